@@ -1,66 +1,297 @@
+mod message;
 mod modbus;
+mod mqtt;
 mod serial;
+mod session;
+mod socket;
 
 use eframe::egui::{self};
+use serde::{Deserialize, Serialize};
 use serial::app::SerialTool;
+use session::{Session, AUTOSAVE_INTERVAL};
+use std::thread::JoinHandle;
+use std::time::Instant;
 
 use crate::modbus::app::ModbusTool;
+use crate::mqtt::app::MqttTool;
+use crate::socket::app::SocketTool;
 
 const APP_FULL: &str = concat!("IoT Toolbox", " ", "V1.0.0");
 
+// eframe storage keys
+const SERIAL_SPLIT_KEY: &str = "serial_split";
+const TAB_KEY: &str = "tab";
+const THEME_KEY: &str = "theme";
+const SERIAL_MAX_LINES_KEY: &str = "serial_max_log_lines";
+const SERIAL_ENTER_SENDS_KEY: &str = "serial_enter_sends";
+const SERIAL_READ_BUFFER_KEY: &str = "serial_read_buffer";
+
 fn main() {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1280.0, 720.0]), // 720p
+        // reopen at the last size and position, 720p is only the first-run size
+        persist_window: true,
         ..Default::default()
     };
     let _ = eframe::run_native(
         APP_FULL,
         options,
-        Box::new(|_cc| Ok(Box::new(AppState::default()))),
+        Box::new(|cc| Ok(Box::new(AppState::new(cc)))),
     );
 }
 
-#[derive(PartialEq, Default)]
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 enum MainTab {
     #[default]
     Serial,
     Modbus,
+    Mqtt,
+    Socket,
 }
 
 struct AppState {
     tab: MainTab,
+    // one look for every tab, applied at the top of each frame
+    theme: egui::ThemePreference,
     serial: SerialTool,
     modbus: ModbusTool,
+    mqtt: MqttTool,
+    socket: SocketTool,
+    // autosave found at startup, waiting for the user to recover or discard it
+    pending_recovery: Option<Session>,
+    last_autosave: Instant,
+    // the autosave still being written, if any
+    autosave_write: Option<JoinHandle<()>>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
         AppState {
             tab: MainTab::Serial,
+            theme: egui::ThemePreference::System,
             serial: SerialTool::new(),
             modbus: ModbusTool::new(),
+            mqtt: MqttTool::new(),
+            socket: SocketTool::new(),
+            pending_recovery: Session::load(),
+            last_autosave: Instant::now(),
+            autosave_write: None,
+        }
+    }
+}
+
+impl AppState {
+    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = AppState::default();
+        if let Some(storage) = cc.storage {
+            if let Some(width) = eframe::get_value(storage, SERIAL_SPLIT_KEY) {
+                app.serial.settings_width = width;
+            }
+            if let Some(lines) = eframe::get_value(storage, SERIAL_MAX_LINES_KEY) {
+                app.serial.max_log_lines = lines;
+            }
+            if let Some(enter_sends) = eframe::get_value(storage, SERIAL_ENTER_SENDS_KEY) {
+                app.serial.enter_sends = enter_sends;
+            }
+            if let Some(size) = eframe::get_value(storage, SERIAL_READ_BUFFER_KEY) {
+                app.serial.read_buffer_size = size;
+            }
+            if let Some(tab) = eframe::get_value(storage, TAB_KEY) {
+                app.tab = tab;
+            }
+            if let Some(theme) = eframe::get_value(storage, THEME_KEY) {
+                app.theme = theme;
+            }
+        }
+        app
+    }
+
+    fn capture_session(&self) -> Session {
+        Session {
+            saved_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+
+            serial_port: self.serial.port_settings.selected_port.clone(),
+            serial_baud_rate: self.serial.port_settings.baud_rate,
+            serial_logs: self.serial.logs.clone(),
+
+            modbus_ip: self.modbus.tcp_ip.clone(),
+            modbus_port: self.modbus.tcp_port,
+            modbus_slave_id: self.modbus.slave_id,
+            modbus_function: Some(self.modbus.function),
+            modbus_address: self.modbus.address,
+            modbus_quantity: self.modbus.quantity,
+            modbus_data: self.modbus.data.clone(),
+            modbus_logs: self.modbus.logs.clone(),
+        }
+    }
+
+    fn restore_session(&mut self, session: Session) {
+        self.serial.port_settings.selected_port = session.serial_port;
+        if session.serial_baud_rate > 0 {
+            self.serial.port_settings.baud_rate = session.serial_baud_rate;
+        }
+        self.serial.logs = session.serial_logs;
+        self.serial.trim_logs();
+
+        if !session.modbus_ip.is_empty() {
+            self.modbus.tcp_ip = session.modbus_ip;
+            self.modbus.tcp_port = session.modbus_port;
+        }
+        self.modbus.slave_id = session.modbus_slave_id.clamp(1, 247);
+        if let Some(function) = session.modbus_function {
+            self.modbus.function = function;
+        }
+        self.modbus.address = session.modbus_address;
+        // writes take their quantity from the values, keep the read one
+        if !self.modbus.function.is_write() {
+            self.modbus.quantity = session
+                .modbus_quantity
+                .clamp(1, self.modbus.function.max_quantity());
+        }
+        self.modbus.data = session.modbus_data;
+        self.modbus.logs = session.modbus_logs;
+    }
+
+    fn ui_recovery(&mut self, ctx: &egui::Context) {
+        let Some(session) = &self.pending_recovery else {
+            return;
+        };
+
+        let mut choice = None;
+        egui::Window::new("Recover session")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("The previous session did not exit cleanly.");
+                ui.label(format!(
+                    "Last autosave: {} ({} serial / {} Modbus log lines)",
+                    session.saved_at,
+                    session.serial_logs.len(),
+                    session.modbus_logs.len()
+                ));
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(egui::RichText::new("Recover").color(egui::Color32::BLUE))
+                        .clicked()
+                    {
+                        choice = Some(true);
+                    }
+                    if ui.button("Discard").clicked() {
+                        choice = Some(false);
+                    }
+                });
+            });
+
+        match choice {
+            Some(true) => {
+                if let Some(session) = self.pending_recovery.take() {
+                    self.restore_session(session);
+                }
+            }
+            Some(false) => {
+                self.pending_recovery = None;
+                Session::clear();
+            }
+            None => {}
         }
     }
+
+    fn autosave(&mut self, ctx: &egui::Context) {
+        // don't overwrite the snapshot the user hasn't decided about yet
+        if self.pending_recovery.is_some() {
+            return;
+        }
+
+        // a slow disk may still be on the last one, two writers would race
+        // on the temp file
+        let writing = self
+            .autosave_write
+            .as_ref()
+            .is_some_and(|write| !write.is_finished());
+        if self.last_autosave.elapsed() >= AUTOSAVE_INTERVAL && !writing {
+            self.autosave_write = self.capture_session().save_in_background();
+            self.last_autosave = Instant::now();
+        }
+        ctx.request_repaint_after(AUTOSAVE_INTERVAL);
+    }
 }
 
 impl eframe::App for AppState {
     fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
+        ctx.set_theme(self.theme);
+        self.serial.tick(ctx);
+        self.mqtt.tick(ctx);
+        let shown = self.tab;
+
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.tab, MainTab::Serial, "Serial");
                 ui.selectable_value(&mut self.tab, MainTab::Modbus, "Modbus");
+                ui.selectable_value(&mut self.tab, MainTab::Mqtt, "MQTT");
+                ui.selectable_value(&mut self.tab, MainTab::Socket, "Socket");
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.selectable_value(&mut self.theme, egui::ThemePreference::Dark, "Dark");
+                    ui.selectable_value(&mut self.theme, egui::ThemePreference::Light, "Light");
+                    ui.selectable_value(&mut self.theme, egui::ThemePreference::System, "System");
+                    ui.label("Theme");
+                });
             });
         });
 
+        // Modbus RTU may want the same port, hand it over when that tab opens;
+        // the other tabs leave the serial session running
+        if self.tab != shown && self.tab == MainTab::Modbus {
+            self.serial.disconnect();
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| match self.tab {
             MainTab::Serial => {
                 self.modbus.stop_auto_poll();
                 self.serial.ui(ctx);
             }
-            MainTab::Modbus => {
-                self.serial.disconnect();
-                self.modbus.ui(ui)
+            MainTab::Modbus => self.modbus.ui(ui),
+            MainTab::Mqtt => {
+                self.modbus.stop_auto_poll();
+                self.mqtt.ui(ui)
+            }
+            MainTab::Socket => {
+                self.modbus.stop_auto_poll();
+                self.socket.ui(ui)
             }
         });
+
+        self.ui_recovery(ctx);
+        self.autosave(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SERIAL_SPLIT_KEY, &self.serial.settings_width);
+        eframe::set_value(storage, TAB_KEY, &self.tab);
+        eframe::set_value(storage, THEME_KEY, &self.theme);
+        eframe::set_value(storage, SERIAL_MAX_LINES_KEY, &self.serial.max_log_lines);
+        eframe::set_value(storage, SERIAL_ENTER_SENDS_KEY, &self.serial.enter_sends);
+        eframe::set_value(
+            storage,
+            SERIAL_READ_BUFFER_KEY,
+            &self.serial.read_buffer_size,
+        );
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // release the port before the process goes away
+        self.serial.disconnect();
+        self.serial.close_log_file();
+        self.modbus.disconnect();
+        self.mqtt.disconnect();
+        self.socket.disconnect();
+
+        // clean exit, nothing to recover next time; a write still in flight
+        // would bring the file back after the clear
+        if let Some(write) = self.autosave_write.take() {
+            let _ = write.join();
+        }
+        Session::clear();
     }
 }
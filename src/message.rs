@@ -0,0 +1,95 @@
+use crate::modbus::app::{ModbusFunction, PollFailure};
+use crate::serial::baud::BaudCandidate;
+use rumqttc::QoS;
+use std::time::Duration;
+
+// Results sent back from background threads / tasks to the UI thread.
+// Each tool owns one channel of these and drains it once per frame.
+pub enum AppMessage {
+    SerialRx(Vec<u8>),
+    // reader stopped on a real I/O error (adapter unplugged, ...)
+    SerialLost(String),
+    // reads that filled the whole buffer since the last report, with its size
+    SerialBufferFull {
+        reads: u32,
+        buffer_size: usize,
+    },
+    BaudScanProgress(u32),
+    BaudScanDone(Vec<BaudCandidate>),
+    ModbusConnected(Result<(), String>),
+    ModbusData {
+        // None for the main read, else the id of the poll block
+        block: Option<u64>,
+        data: Vec<u16>,
+        elapsed: Duration,
+    },
+    ModbusWritten {
+        address: u16,
+        values: Vec<u16>,
+        elapsed: Duration,
+    },
+    // a write followed by a read of the same range on the same link
+    ModbusVerified {
+        address: u16,
+        written: Vec<u16>,
+        read: Vec<u16>,
+        elapsed: Duration,
+    },
+    ModbusRetry {
+        attempt: u32,
+        retries: u32,
+        delay: Duration,
+    },
+    ModbusError {
+        function: ModbusFunction,
+        address: u16,
+        quantity: u16,
+        kind: PollFailure,
+        message: String,
+        elapsed: Duration,
+    },
+    // one slave id probed by the bus scan; failure is None when it answered with data
+    ModbusScanned {
+        slave: u8,
+        failure: Option<(PollFailure, String)>,
+        elapsed: Duration,
+    },
+    ModbusScanDone,
+    // a range read by the register sweep: its data, or why it had none
+    ModbusSwept {
+        address: u16,
+        quantity: u16,
+        result: Result<Vec<u16>, String>,
+    },
+    ModbusSweepDone,
+    // bytes as written to / read from the Modbus transport
+    ModbusFrame {
+        sent: bool,
+        bytes: Vec<u8>,
+    },
+    MqttConnected,
+    // the connection failed or dropped; retry_in is None when the event loop
+    // stopped for good, else it polls (and so reconnects) after that wait
+    MqttLost {
+        error: String,
+        retry_in: Option<Duration>,
+    },
+    MqttMessage {
+        topic: String,
+        payload: Vec<u8>,
+        qos: QoS,
+        retain: bool,
+    },
+    // a publish went out with this packet id, 0 for QoS 0
+    MqttPublished(u16),
+    // broker acknowledgements: PUBACK ends QoS 1, PUBREC then PUBCOMP QoS 2
+    MqttPubAck(u16),
+    MqttPubRec(u16),
+    MqttPubComp(u16),
+    SocketConnected(Result<(), String>),
+    SocketRx(Vec<u8>),
+    // bytes the socket task has written
+    SocketSent(usize),
+    // peer closed the connection or an I/O error ended it
+    SocketLost(String),
+}
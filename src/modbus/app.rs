@@ -1,31 +1,688 @@
-use super::display::DisplayFormat;
+use super::config::{ModbusConfig, CONFIG_FILE};
+use super::display::{
+    bit_ruler, format_number, group_bits, inspect, ByteOrder, DisplayFormat, MAX_DECIMALS,
+};
+use super::favorites::{Favorite, Favorites, FAVORITES_FILE};
+use super::frames::{FrameLog, FrameTap, Tapped};
+use super::register_map::{RegisterMap, RegisterMapEditor, REGISTER_MAP_FILE};
+#[cfg(feature = "link-sim")]
+use super::sim::LinkSim;
+use super::snapshot::{Snapshot, SnapshotRow, SNAPSHOT_FILE};
+use super::sweep::{self, populated_blocks};
+use super::tls::TlsSettings;
+use super::trend::Trend;
+use crate::message::AppMessage;
+use crate::serial::settings::{serial_settings_ui, SerialPortSettings};
 use anyhow::{Error, Result};
-use serialport::{DataBits, Parity, StopBits};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::channel;
-use std::sync::mpsc::{Receiver, Sender};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex};
 use tokio_modbus::prelude::*;
+use tokio_modbus::ExceptionCode;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum ModbusMode {
     Tcp,
+    Tls,
     Rtu,
+    // RTU frames (with CRC) over a raw TCP socket, as some serial gateways do
+    RtuOverTcp,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+// everything the background task needs to open a connection
+#[derive(Clone)]
+pub enum Endpoint {
+    Tcp {
+        ip: String,
+        port: u16,
+    },
+    Tls {
+        ip: String,
+        port: u16,
+        tls: TlsSettings,
+    },
+    RtuOverTcp {
+        ip: String,
+        port: u16,
+    },
+    Rtu {
+        builder: serialport::SerialPortBuilder,
+    },
+}
+
+impl Endpoint {
+    async fn connect(&self, tap: Option<&FrameTap>) -> Result<client::Context, Error> {
+        match self {
+            Endpoint::Tcp { ip, port } => {
+                let socket_addr: SocketAddr = format!("{}:{}", ip, port).parse()?;
+                let stream = tokio::net::TcpStream::connect(socket_addr).await?;
+                Ok(tcp::attach(Tapped::new(stream, tap)))
+            }
+            Endpoint::Tls { ip, port, tls } => {
+                let stream = tls.connect(ip, *port).await?;
+                Ok(tcp::attach(Tapped::new(stream, tap)))
+            }
+            Endpoint::RtuOverTcp { ip, port } => {
+                let socket_addr: SocketAddr = format!("{}:{}", ip, port).parse()?;
+                let stream = tokio::net::TcpStream::connect(socket_addr).await?;
+                Ok(rtu::attach(Tapped::new(stream, tap)))
+            }
+            Endpoint::Rtu { builder } => {
+                let stream = tokio_serial::SerialStream::open(builder)?;
+                Ok(rtu::attach(Tapped::new(stream, tap)))
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum ModbusFunction {
-    ReadCoils,    // 01
-    ReadDiscrete, // 02
-    ReadHolding,  // 03
-    ReadInput,    // 04
+    ReadCoils,              // 01
+    ReadDiscrete,           // 02
+    ReadHolding,            // 03
+    ReadInput,              // 04
+    WriteSingleCoil,        // 05
+    WriteSingleRegister,    // 06
+    WriteMultipleCoils,     // 15
+    WriteMultipleRegisters, // 16
+}
+
+impl ModbusFunction {
+    pub const ALL: [ModbusFunction; 8] = [
+        ModbusFunction::ReadCoils,
+        ModbusFunction::ReadDiscrete,
+        ModbusFunction::ReadHolding,
+        ModbusFunction::ReadInput,
+        ModbusFunction::WriteSingleCoil,
+        ModbusFunction::WriteSingleRegister,
+        ModbusFunction::WriteMultipleCoils,
+        ModbusFunction::WriteMultipleRegisters,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ModbusFunction::ReadCoils => "01 Read Coils(0x)",
+            ModbusFunction::ReadDiscrete => "02 Read Discrete Inputs(1x)",
+            ModbusFunction::ReadHolding => "03 Read Holding Registers(4x)",
+            ModbusFunction::ReadInput => "04 Read Input Registers(3x)",
+            ModbusFunction::WriteSingleCoil => "05 Write Single Coil(0x)",
+            ModbusFunction::WriteSingleRegister => "06 Write Single Register(4x)",
+            ModbusFunction::WriteMultipleCoils => "15 Write Multiple Coils(0x)",
+            ModbusFunction::WriteMultipleRegisters => "16 Write Multiple Registers(4x)",
+        }
+    }
+
+    // leading digit of the classic Modicon reference (0x / 1x / 3x / 4x)
+    pub fn plc_prefix(&self) -> u32 {
+        match self {
+            ModbusFunction::ReadCoils
+            | ModbusFunction::WriteSingleCoil
+            | ModbusFunction::WriteMultipleCoils => 0,
+            ModbusFunction::ReadDiscrete => 1,
+            ModbusFunction::ReadInput => 3,
+            ModbusFunction::ReadHolding
+            | ModbusFunction::WriteSingleRegister
+            | ModbusFunction::WriteMultipleRegisters => 4,
+        }
+    }
+
+    pub fn is_write(&self) -> bool {
+        matches!(
+            self,
+            ModbusFunction::WriteSingleCoil
+                | ModbusFunction::WriteSingleRegister
+                | ModbusFunction::WriteMultipleCoils
+                | ModbusFunction::WriteMultipleRegisters
+        )
+    }
+
+    // per request, as the spec allows (coils pack 8 to a byte)
+    pub fn max_quantity(&self) -> u16 {
+        match self {
+            ModbusFunction::ReadCoils | ModbusFunction::ReadDiscrete => 2000,
+            ModbusFunction::ReadHolding | ModbusFunction::ReadInput => 125,
+            ModbusFunction::WriteSingleCoil | ModbusFunction::WriteSingleRegister => 1,
+            ModbusFunction::WriteMultipleCoils => 1968,
+            ModbusFunction::WriteMultipleRegisters => 123,
+        }
+    }
+
+    // the read that shows what a write left behind
+    pub fn read_back(&self) -> Option<ModbusFunction> {
+        match self {
+            ModbusFunction::WriteSingleCoil | ModbusFunction::WriteMultipleCoils => {
+                Some(ModbusFunction::ReadCoils)
+            }
+            ModbusFunction::WriteSingleRegister | ModbusFunction::WriteMultipleRegisters => {
+                Some(ModbusFunction::ReadHolding)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_coil(&self) -> bool {
+        matches!(
+            self,
+            ModbusFunction::WriteSingleCoil | ModbusFunction::WriteMultipleCoils
+        )
+    }
+
+    // reads that return one bit per address
+    fn reads_bits(&self) -> bool {
+        matches!(
+            self,
+            ModbusFunction::ReadCoils | ModbusFunction::ReadDiscrete
+        )
+    }
+
+    fn is_single(&self) -> bool {
+        matches!(
+            self,
+            ModbusFunction::WriteSingleCoil | ModbusFunction::WriteSingleRegister
+        )
+    }
+}
+
+// "1, -2, 0x10" -> [1, 0xFFFE, 0x10]; coils take 0 / 1
+pub fn parse_write_values(text: &str, function: ModbusFunction) -> Result<Vec<u16>, String> {
+    let values = text
+        .split([',', ' ', '\n'])
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| {
+            let s = s.trim();
+            let value = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                Some(hex) => i32::from_str_radix(hex, 16),
+                None => s.parse::<i32>(),
+            }
+            .map_err(|_| format!("Invalid value '{}'", s))?;
+
+            if function.is_coil() && !(0..=1).contains(&value) {
+                return Err(format!("Coil value must be 0 or 1, got {}", value));
+            }
+            if !(i16::MIN as i32..=u16::MAX as i32).contains(&value) {
+                return Err(format!("Value {} does not fit a register", value));
+            }
+            Ok(value as u16)
+        })
+        .collect::<Result<Vec<u16>, String>>()?;
+
+    let max = function.max_quantity() as usize;
+    match values.len() {
+        0 => Err("Nothing to write".into()),
+        n if function.is_single() && n != 1 => Err(format!("Expected 1 value, got {}", n)),
+        n if n > max => Err(format!("At most {} values per write, got {}", max, n)),
+        _ => Ok(values),
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum AddressNotation {
+    Protocol, // 0-based wire address
+    Plc,      // 40001 style, 1-based
+}
+
+impl AddressNotation {
+    // `offset` only shifts the shown number, the wire address is untouched
+    pub fn format(&self, function: ModbusFunction, address: u16, offset: i32) -> String {
+        let shown = address as i64 + offset as i64;
+        match self {
+            AddressNotation::Protocol => shown.to_string(),
+            AddressNotation::Plc => {
+                let n = shown + 1;
+                if n < 0 {
+                    n.to_string()
+                } else if n <= 9999 {
+                    format!("{}{:04}", function.plc_prefix(), n)
+                } else {
+                    format!("{}{:05}", function.plc_prefix(), n)
+                }
+            }
+        }
+    }
+}
+
+// A read polled along with the main function / address / quantity, for
+// devices whose interesting registers are scattered.
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct PollBlock {
+    pub function: ModbusFunction,
+    pub address: u16,
+    pub quantity: u16,
+}
+
+// What was last read from one of `blocks`. The id tags that block's results,
+// so a read still in flight when its block is removed or edited is dropped
+// instead of landing in whichever block took its place.
+pub struct BlockData {
+    pub id: u64,
+    pub data: Vec<u16>,
+}
+
+// One client session shared by every read and write until Disconnect. The
+// context is opened on first use and reopened when a transport error drops it.
+#[derive(Clone)]
+pub struct Link {
+    endpoint: Endpoint,
+    ctx: Arc<Mutex<Option<client::Context>>>,
+    retry: RetryPolicy,
+    // bound connecting and each request, an unreachable host otherwise
+    // hangs for the OS default
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    // a probe expects silence, so its timeouts keep the session open
+    keep_on_timeout: bool,
+    // raw frame log, if the UI wants one
+    tap: Option<FrameTap>,
+    // flipped by close, aborts whatever request or backoff is in flight
+    closed: Arc<watch::Sender<bool>>,
+    // faults injected under every request, shared with the settings UI
+    #[cfg(feature = "link-sim")]
+    sim: Arc<std::sync::Mutex<LinkSim>>,
+}
+
+// attempts after the first failure; the delay doubles after each one
+#[derive(Clone, Copy, Default)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay * 2u32.saturating_pow(attempt - 1)
+    }
+}
+
+impl Link {
+    pub fn new(endpoint: Endpoint) -> Self {
+        Link {
+            endpoint,
+            ctx: Arc::new(Mutex::new(None)),
+            retry: RetryPolicy::default(),
+            connect_timeout: Duration::from_secs(3),
+            request_timeout: Duration::from_secs(3),
+            keep_on_timeout: false,
+            tap: None,
+            closed: Arc::new(watch::Sender::new(false)),
+            #[cfg(feature = "link-sim")]
+            sim: Arc::new(std::sync::Mutex::new(LinkSim::new())),
+        }
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_frame_tap(mut self, tap: FrameTap) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self.request_timeout = timeout;
+        self
+    }
+
+    // only the wait for an answer, connecting keeps its own bound
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    pub fn probing(mut self) -> Self {
+        self.keep_on_timeout = true;
+        self
+    }
+
+    #[cfg(feature = "link-sim")]
+    pub fn with_sim(mut self, sim: Arc<std::sync::Mutex<LinkSim>>) -> Self {
+        self.sim = sim;
+        self
+    }
+
+    pub async fn open(&self) -> Result<(), Error> {
+        let mut ctx = self.ctx.lock().await;
+        if ctx.is_none() {
+            *ctx = Some(self.connect().await?);
+        }
+        Ok(())
+    }
+
+    async fn connect(&self) -> Result<client::Context, Error> {
+        let connect = self.endpoint.connect(self.tap.as_ref());
+        self.within(self.connect_timeout, "connect timeout", connect)
+            .await
+    }
+
+    // a TimedOut io error, so it is classified (and retried) as a timeout
+    async fn within<T>(
+        &self,
+        timeout: Duration,
+        what: &str,
+        fut: impl std::future::Future<Output = Result<T, Error>>,
+    ) -> Result<T, Error> {
+        match tokio::time::timeout(timeout, fut).await {
+            Ok(result) => result,
+            Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, what).into()),
+        }
+    }
+
+    pub async fn close(&self) {
+        self.closed.send_replace(true);
+        if let Some(mut ctx) = self.ctx.lock().await.take() {
+            let _ = ctx.disconnect().await;
+        }
+    }
+
+    // timeouts and transport errors are retried, exception responses are final
+    pub async fn exec_with_retry(
+        &self,
+        on_retry: impl Fn(u32, Duration),
+        slave_id: u8,
+        function: ModbusFunction,
+        address: u16,
+        quantity: u16,
+        values: &[u16],
+    ) -> Result<Vec<u16>, Error> {
+        let mut closed = self.closed.subscribe();
+        let mut attempt = 0;
+
+        loop {
+            let result = tokio::select! {
+                result = self.exec(slave_id, function, address, quantity, values) => result,
+                _ = closed.wait_for(|c| *c) => return Err(Self::cancelled()),
+            };
+
+            match result {
+                Err(e) => {
+                    self.backoff(e, &mut attempt, &on_retry, &mut closed)
+                        .await?
+                }
+                data => return data,
+            }
+        }
+    }
+
+    // a write and the read of the same range under one hold of the session,
+    // so no poll or block read lands in between. The outer error is the
+    // write's, the inner result the read-back's.
+    pub async fn exec_pair(
+        &self,
+        on_retry: impl Fn(u32, Duration),
+        slave_id: u8,
+        function: ModbusFunction,
+        address: u16,
+        values: &[u16],
+        read_back: ModbusFunction,
+    ) -> Result<Result<Vec<u16>, Error>, Error> {
+        let write = PollBlock {
+            function,
+            address,
+            quantity: values.len() as u16,
+        };
+        let read = PollBlock {
+            function: read_back,
+            ..write
+        };
+
+        let mut guard = self.ctx.lock().await;
+        self.retry_locked(&mut guard, &on_retry, slave_id, write, values)
+            .await?;
+        Ok(self
+            .retry_locked(&mut guard, &on_retry, slave_id, read, &[])
+            .await)
+    }
+
+    // exec_with_retry for a caller already holding the session, which it
+    // keeps through the backoff
+    async fn retry_locked(
+        &self,
+        session: &mut Option<client::Context>,
+        on_retry: &impl Fn(u32, Duration),
+        slave_id: u8,
+        request: PollBlock,
+        values: &[u16],
+    ) -> Result<Vec<u16>, Error> {
+        let PollBlock {
+            function,
+            address,
+            quantity,
+        } = request;
+        let mut closed = self.closed.subscribe();
+        let mut attempt = 0;
+
+        loop {
+            let result = tokio::select! {
+                result = self.exec_locked(session, slave_id, function, address, quantity, values) => result,
+                _ = closed.wait_for(|c| *c) => return Err(Self::cancelled()),
+            };
+
+            match result {
+                Err(e) => {
+                    self.backoff(e, &mut attempt, &on_retry, &mut closed)
+                        .await?
+                }
+                data => return data,
+            }
+        }
+    }
+
+    // waits out the delay before the next attempt; the error comes back
+    // when it is final or Disconnect came first
+    async fn backoff(
+        &self,
+        error: Error,
+        attempt: &mut u32,
+        on_retry: &impl Fn(u32, Duration),
+        closed: &mut watch::Receiver<bool>,
+    ) -> Result<(), Error> {
+        if *attempt >= self.retry.retries || PollFailure::classify(&error) == PollFailure::Exception
+        {
+            return Err(error);
+        }
+        *attempt += 1;
+        let delay = self.retry.delay(*attempt);
+        on_retry(*attempt, delay);
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => Ok(()),
+            _ = closed.wait_for(|c| *c) => Err(Self::cancelled()),
+        }
+    }
+
+    fn cancelled() -> Error {
+        std::io::Error::new(std::io::ErrorKind::Interrupted, "cancelled by Disconnect").into()
+    }
+
+    pub async fn exec(
+        &self,
+        slave_id: u8,
+        function: ModbusFunction,
+        address: u16,
+        quantity: u16,
+        values: &[u16],
+    ) -> Result<Vec<u16>, Error> {
+        let mut guard = self.ctx.lock().await;
+        self.exec_locked(&mut guard, slave_id, function, address, quantity, values)
+            .await
+    }
+
+    async fn exec_locked(
+        &self,
+        guard: &mut Option<client::Context>,
+        slave_id: u8,
+        function: ModbusFunction,
+        address: u16,
+        quantity: u16,
+        values: &[u16],
+    ) -> Result<Vec<u16>, Error> {
+        let reused = guard.is_some();
+
+        let ctx = match guard.as_mut() {
+            Some(ctx) => ctx,
+            None => guard.insert(self.connect().await?),
+        };
+        let mut result = self
+            .request(ctx, slave_id, function, address, quantity, values)
+            .await;
+
+        // the device or a gateway may have closed an idle session, retry once on a fresh one
+        if reused && matches!(&result, Err(e) if PollFailure::classify(e) == PollFailure::Transport)
+        {
+            // a failed reconnect must not leave the dead context for the next call
+            *guard = None;
+            let ctx = guard.insert(self.connect().await?);
+            result = self
+                .request(ctx, slave_id, function, address, quantity, values)
+                .await;
+        }
+
+        // after a timeout or transport error the stream state is unknown,
+        // unless silence is the expected answer
+        let drop = match &result {
+            Err(e) => match PollFailure::classify(e) {
+                PollFailure::Exception => false,
+                PollFailure::Timeout => !self.keep_on_timeout,
+                PollFailure::Transport => true,
+            },
+            Ok(_) => false,
+        };
+        if drop {
+            *guard = None;
+        }
+        result
+    }
+
+    // one request on an open context; simulated faults happen inside the
+    // timeout, so a dropped answer times out and is retried like a real one
+    async fn request(
+        &self,
+        ctx: &mut client::Context,
+        slave_id: u8,
+        function: ModbusFunction,
+        address: u16,
+        quantity: u16,
+        values: &[u16],
+    ) -> Result<Vec<u16>, Error> {
+        let request = ModbusTool::modbus_exec(ctx, slave_id, function, address, quantity, values);
+        #[cfg(feature = "link-sim")]
+        let request = (*self.sim.lock().unwrap()).apply(request);
+        self.within(self.request_timeout, "request timeout", request)
+            .await
+    }
+}
+
+// why a poll produced no data, so the log tells timeouts apart from device answers
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PollFailure {
+    Timeout,
+    Exception,
+    Transport,
+}
+
+impl PollFailure {
+    pub fn classify(e: &Error) -> Self {
+        if e.downcast_ref::<ExceptionCode>().is_some() {
+            return PollFailure::Exception;
+        }
+        if e.downcast_ref::<tokio::time::error::Elapsed>().is_some() {
+            return PollFailure::Timeout;
+        }
+
+        let io = e
+            .downcast_ref::<std::io::Error>()
+            .or(match e.downcast_ref() {
+                Some(tokio_modbus::Error::Transport(io)) => Some(io),
+                _ => None,
+            });
+        match io {
+            Some(io) if io.kind() == std::io::ErrorKind::TimedOut => PollFailure::Timeout,
+            _ => PollFailure::Transport,
+        }
+    }
+
+    // where to look: the link, the device, or both
+    pub fn origin(&self) -> &'static str {
+        match self {
+            PollFailure::Timeout => "no response from the device",
+            PollFailure::Exception => "protocol: the device rejected the request",
+            PollFailure::Transport => "network: the connection failed",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PollFailure::Timeout => "timeout",
+            PollFailure::Exception => "exception",
+            PollFailure::Transport => "transport error",
+        }
+    }
+}
+
+// "02 Illegal Data Address", code as on the wire plus the spec name
+pub fn exception_text(code: &ExceptionCode) -> String {
+    let (n, name) = match code {
+        ExceptionCode::IllegalFunction => (0x01, "Illegal Function"),
+        ExceptionCode::IllegalDataAddress => (0x02, "Illegal Data Address"),
+        ExceptionCode::IllegalDataValue => (0x03, "Illegal Data Value"),
+        ExceptionCode::ServerDeviceFailure => (0x04, "Server Device Failure"),
+        ExceptionCode::Acknowledge => (0x05, "Acknowledge"),
+        ExceptionCode::ServerDeviceBusy => (0x06, "Server Device Busy"),
+        ExceptionCode::MemoryParityError => (0x08, "Memory Parity Error"),
+        ExceptionCode::GatewayPathUnavailable => (0x0A, "Gateway Path Unavailable"),
+        ExceptionCode::GatewayTargetDevice => (0x0B, "Gateway Target Device Failed to Respond"),
+        ExceptionCode::Custom(n) => (*n, "Unknown Exception"),
+    };
+    format!("{:02X} {}", n, name)
 }
 
+// polls the rolling latency average is taken over
+const LATENCY_WINDOW: usize = 20;
+
+// quiet period after the last slave/function/address edit before auto-read fires
+const AUTO_READ_DEBOUNCE: Duration = Duration::from_millis(300);
+
 pub struct ModbusRow {
+    // None for the main read, else the index into `blocks`
+    pub block: Option<usize>,
     pub index: usize,
     pub address: u16,
+    pub name: String,
     pub raw: Vec<u16>, // original
     pub format: DisplayFormat,
     pub value: String,
+    // value without unit or nibble grouping, the starting text of an edit
+    pub plain: String,
+    // decoded value before scale / offset, when a transform applies
+    pub unscaled: Option<String>,
+    pub scale: f64,
+    pub offset: f64,
+    pub valid: bool,
+    pub lookup: Option<String>,
+}
+
+// A write to a scaled register, held until the user has seen the raw value
+// the typed engineering value turns into.
+pub struct PendingWrite {
+    pub address: u16,
+    pub format: DisplayFormat,
+    // as typed, in engineering units
+    pub entered: f64,
+    pub scale: f64,
+    pub offset: f64,
+    // (entered - offset) / scale, before rounding
+    pub exact: f64,
+    // what the format encodes: exact, or rounded for integer formats
+    pub raw: f64,
+    pub values: Vec<u16>,
 }
 
 pub struct ModbusTool {
@@ -35,39 +692,130 @@ pub struct ModbusTool {
     pub tcp_ip: String,
     pub tcp_port: u16,
 
-    pub available_ports: Vec<String>,
-    pub selected_port: Option<String>,
-    pub baud_rate: u32,
-    pub data_bits: DataBits,
-    pub parity: Parity,
-    pub stop_bits: StopBits,
+    pub tls_port: u16,
+    pub tls: TlsSettings,
+
+    // retries for timeouts / transport errors, backoff doubles from retry_delay_ms
+    pub retries: u32,
+    pub retry_delay_ms: u64,
+    // for connecting and for each request
+    pub timeout_ms: u64,
+
+    #[cfg(feature = "link-sim")]
+    pub link_sim: Arc<std::sync::Mutex<LinkSim>>,
+
+    pub port_settings: SerialPortSettings,
 
     pub slave_id: u8,
     pub function: ModbusFunction,
     pub address: u16,
     pub quantity: u16,
+    // values for the write functions, e.g. "1, 2, 3"
+    pub write_values: String,
+    // outcome of the last Write + Verify, shown next to the button
+    pub last_verify: Option<Result<String, String>>,
+    // extra reads, each with its own table section
+    pub blocks: Vec<PollBlock>,
+
+    // opt-in: read automatically once parameter edits settle
+    pub auto_read: bool,
+    auto_read_due: Option<Instant>,
 
     pub view_rows: usize,
+    pub address_notation: AddressNotation,
     pub display_format: DisplayFormat,
+    // per-address overrides of display_format
+    pub row_formats: BTreeMap<u16, DisplayFormat>,
+    pub byte_order: ByteOrder,
+    // digits after the point for Float / Double
+    pub decimals: usize,
+    pub binary_grouped: bool,
+    pub binary_indices: bool,
 
     pub data: Vec<u16>,
+    // last read of each of `blocks`
+    pub block_data: Vec<BlockData>,
+    next_block_id: u64,
+    // round trips of the latest successful reads, newest last
+    pub latencies: VecDeque<Duration>,
+    // holding register cell being edited: (block id, address, text), the
+    // same address can show up in the main read and in a block
+    pub editing: Option<(Option<u64>, u16, String)>,
+    // scaled write waiting for Write / Cancel
+    pub pending_write: Option<PendingWrite>,
+
+    pub register_map: RegisterMap,
+    pub map_editor: RegisterMapEditor,
+
+    pub favorites: Favorites,
+    pub favorite_name: String,
+
+    pub trend: Trend,
+    pub frames: FrameLog,
+
+    pub config_path: String,
+    // where Export JSON writes the table snapshot
+    pub export_path: String,
 
     pub logs: Vec<String>,
     pub scroll_to_bottom: bool,
 
-    pub rx: Option<Receiver<Vec<u16>>>,
-    pub rt: tokio::runtime::Runtime,
+    // set between Connect and Disconnect
+    pub link: Option<Link>,
+
+    tx: Sender<AppMessage>,
+    rx: Receiver<AppMessage>,
+    // None when the runtime failed to start; the tab is then disabled
+    pub rt: Option<tokio::runtime::Runtime>,
     pub stop_tx: Option<Sender<()>>,
-    pub status_rx: Option<Receiver<String>>,
+    // the running poll re-reads this every cycle
+    pub poll_interval_ms: u64,
+    poll_interval: Arc<AtomicU64>,
+    // polls in a row without an answer; past stale_after the table is greyed out
+    poll_failures: u32,
+    pub stale_after: u32,
+    // the stale warning went out for this run of failures
+    stale_warned: bool,
+
+    // bus scan: read 1 holding register from each slave id in turn
+    pub scan_timeout_ms: u64,
+    scan_stop: Option<Sender<()>>,
+    // last id probed, for the progress bar
+    scan_at: u8,
+    scan_results: Vec<(u8, Option<(PollFailure, String)>)>,
+
+    // register sweep: read sweep_from..=sweep_to in chunks, keep what isn't zero
+    pub sweep_function: ModbusFunction,
+    pub sweep_from: u16,
+    pub sweep_to: u16,
+    sweep_stop: Option<Sender<()>>,
+    // addresses covered so far, answered or not, out of sweep_total
+    sweep_done: u32,
+    sweep_total: u32,
+    sweep_values: BTreeMap<u16, u16>,
+    sweep_zeros: u32,
+    // (address, quantity, error) of ranges that could not be read
+    sweep_errors: Vec<(u16, u16, String)>,
 
     pub status: String,
 }
 
 impl ModbusTool {
     pub fn new() -> Self {
-        let available_ports = serialport::available_ports()
-            .map(|ports| ports.into_iter().map(|p| p.port_name).collect())
-            .unwrap_or_default();
+        let (rt, status) = match tokio::runtime::Runtime::new() {
+            Ok(rt) => (Some(rt), "Disconnected".to_string()),
+            Err(e) => (None, format!("Async runtime unavailable: {}", e)),
+        };
+
+        let (tx, rx) = channel::<AppMessage>();
+
+        let register_map = RegisterMap::load_or_default(REGISTER_MAP_FILE);
+        let favorites = Favorites::load_or_default(FAVORITES_FILE);
+        let status = register_map
+            .load_error
+            .clone()
+            .or_else(|| favorites.load_error.clone())
+            .unwrap_or(status);
 
         Self {
             mode: ModbusMode::Tcp,
@@ -77,377 +825,2524 @@ impl ModbusTool {
             tcp_ip: "127.0.0.1".to_string(),
             tcp_port: 502,
 
+            // ===== TLS =====
+            tls_port: 802,
+            tls: TlsSettings::default(),
+
+            retries: 2,
+            retry_delay_ms: 100,
+            timeout_ms: 3000,
+
+            #[cfg(feature = "link-sim")]
+            link_sim: Arc::new(std::sync::Mutex::new(LinkSim::new())),
+
             // ===== RTU =====
-            available_ports,
-            selected_port: None,
-            baud_rate: 9600,
-            data_bits: DataBits::Eight,
-            parity: Parity::None,
-            stop_bits: StopBits::One,
+            port_settings: SerialPortSettings::new(),
 
             // ===== Slave =====
             slave_id: 1,
             function: ModbusFunction::ReadHolding,
             address: 0,
             quantity: 10,
+            write_values: String::new(),
+            last_verify: None,
+            blocks: Vec::new(),
+
+            auto_read: false,
+            auto_read_due: None,
 
             view_rows: 10,
+            address_notation: AddressNotation::Protocol,
             display_format: DisplayFormat::Signed,
+            row_formats: BTreeMap::new(),
+            byte_order: ByteOrder::Abcd,
+            decimals: 3,
+            binary_grouped: true,
+            binary_indices: false,
 
             data: Vec::new(),
+            block_data: Vec::new(),
+            next_block_id: 0,
+            latencies: VecDeque::new(),
+            editing: None,
+            pending_write: None,
+
+            register_map,
+            map_editor: RegisterMapEditor::new(),
+
+            favorites,
+            favorite_name: String::new(),
+
+            trend: Trend::new(),
+            frames: FrameLog::new(),
+
+            config_path: CONFIG_FILE.to_string(),
+            export_path: SNAPSHOT_FILE.to_string(),
 
             logs: Vec::new(),
             scroll_to_bottom: false,
 
-            rx: None,
-            rt: tokio::runtime::Runtime::new().expect("Failed to create tokio runtime"),
+            link: None,
+            tx,
+            rx,
+            rt,
             stop_tx: None,
-            status_rx: None,
-
-            status: "Disconnected".to_string(),
+            poll_interval_ms: 1000,
+            poll_interval: Arc::new(AtomicU64::new(1000)),
+            poll_failures: 0,
+            stale_after: 5,
+            stale_warned: false,
+
+            scan_timeout_ms: 200,
+            scan_stop: None,
+            scan_at: 0,
+            scan_results: Vec::new(),
+
+            sweep_function: ModbusFunction::ReadHolding,
+            sweep_from: 0,
+            sweep_to: 999,
+            sweep_stop: None,
+            sweep_done: 0,
+            sweep_total: 0,
+            sweep_values: BTreeMap::new(),
+            sweep_zeros: 0,
+            sweep_errors: Vec::new(),
+
+            status,
         }
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui) {
+        if self.rt.is_none() {
+            ui.vertical_centered(|ui| {
+                ui.add_space(40.0);
+                ui.label(
+                    egui::RichText::new("Modbus is disabled")
+                        .strong()
+                        .color(egui::Color32::RED),
+                );
+                ui.label(&self.status);
+                ui.label("The Serial tab is unaffected.");
+            });
+            return;
+        }
+
         ui.vertical(|ui| {
             self.ui_connection(ui);
 
             self.ui_slave(ui);
+            self.auto_read_tick(ui.ctx());
 
             self.ui_view(ui);
 
-            self.ui_table(
-                ui,
-                &mut Self::build_rows(
-                    self.address,
-                    &self.data,
-                    self.view_rows,
-                    self.display_format,
-                ),
-            );
+            self.ui_favorites(ui);
 
-            // self.ui_logs(ui);
-            self.ui_status(ui);
-        });
+            self.ui_mapped(ui);
 
-        if let Some(rx) = &self.rx {
-            while let Ok(data) = rx.try_recv() {
-                self.data = data;
-                self.logs.push(format!("RX {} registers", self.data.len()));
-                self.scroll_to_bottom = true;
-            }
-        }
+            egui::CollapsingHeader::new("Trend").show(ui, |ui| self.trend.ui(ui));
+            egui::CollapsingHeader::new("Raw frames").show(ui, |ui| self.frames.ui(ui));
+            egui::CollapsingHeader::new("Slave scan").show(ui, |ui| self.ui_scan(ui));
+            egui::CollapsingHeader::new("Register sweep").show(ui, |ui| self.ui_sweep(ui));
 
-        if let Some(status_rx) = &self.status_rx {
-            while let Ok(status) = status_rx.try_recv() {
-                self.status = status;
-            }
-        }
-    }
+            self.ui_latency(ui);
 
-    fn ui_status(&mut self, ui: &mut egui::Ui) {
-        egui::TopBottomPanel::bottom("modbus_status").show(ui.ctx(), |ui| {
-            ui.horizontal(|ui| {
-                ui.label(egui::RichText::new("Status: ").color(egui::Color32::RED));
-                ui.monospace(&self.status);
+            let mut sections = self.build_sections();
+            let stale = self.poll_failures >= self.stale_after;
+            if stale {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!(
+                        "⚠ No response for {} consecutive polls, the values below are stale",
+                        self.poll_failures
+                    ),
+                );
+            }
+            ui.scope(|ui| {
+                if stale {
+                    ui.multiply_opacity(0.4);
+                }
+                self.ui_table(ui, &mut sections);
             });
-        });
-    }
 
-    fn ui_connection(&mut self, ui: &mut egui::Ui) {
-        egui::Frame::group(ui.style()).show(ui, |ui| {
-            ui.label(egui::RichText::new("Connection").strong());
+            // self.ui_logs(ui);
+            self.ui_status(ui);
+        });
 
-            ui.horizontal(|ui| {
-                ui.selectable_value(&mut self.mode, ModbusMode::Tcp, "TCP");
-                // TODO
-                // ui.selectable_value(&mut self.mode, ModbusMode::Rtu, "RTU");
-            });
+        self.map_editor.ui(ui.ctx(), &mut self.register_map);
+        self.ui_pending_write(ui.ctx());
 
-            ui.separator();
+        let messages: Vec<AppMessage> = self.rx.try_iter().collect();
+        for msg in messages {
+            self.handle_message(msg);
+        }
+    }
 
-            match self.mode {
-                ModbusMode::Tcp => self.ui_tcp(ui),
-                ModbusMode::Rtu => self.ui_rtu(ui),
+    fn handle_message(&mut self, msg: AppMessage) {
+        match msg {
+            // ignore the outcome of a connect that was cancelled by Disconnect
+            AppMessage::ModbusConnected(_) if self.link.is_none() => {}
+            AppMessage::ModbusConnected(Ok(())) => {
+                self.connected = true;
+                self.status = "Connected".into();
+                self.logs.push("Connected".into());
+                self.scroll_to_bottom = true;
             }
-        });
-    }
+            AppMessage::ModbusConnected(Err(e)) => {
+                self.link = None;
+                self.connected = false;
+                self.status = format!("Connect failed: {}", e);
+                self.logs.push(format!("ERR connect: {}", e));
+                self.scroll_to_bottom = true;
+            }
+            AppMessage::ModbusData {
+                block,
+                data,
+                elapsed,
+            } => {
+                self.logs.push(format!(
+                    "RX {} registers in {}ms",
+                    data.len(),
+                    elapsed.as_millis()
+                ));
+                if self.stale_warned {
+                    self.logs.push(format!(
+                        "Responses are back after {} failed polls",
+                        self.poll_failures
+                    ));
+                }
+                self.poll_failures = 0;
+                self.stale_warned = false;
+                match block {
+                    None => {
+                        if self.stop_tx.is_some() {
+                            self.trend.push(self.address, &data, self.byte_order);
+                        }
+                        self.data = data;
+                    }
+                    // a block removed or edited while its read was in flight
+                    // has no slot with that id any more
+                    Some(id) => {
+                        if let Some(slot) = self.block_data.iter_mut().find(|b| b.id == id) {
+                            slot.data = data;
+                        }
+                    }
+                }
 
-    fn ui_tcp(&mut self, ui: &mut egui::Ui) {
-        ui.horizontal(|ui| {
-            ui.label("IP");
-            ui.text_edit_singleline(&mut self.tcp_ip);
+                self.latencies.push_back(elapsed);
+                if self.latencies.len() > LATENCY_WINDOW {
+                    self.latencies.pop_front();
+                }
+                self.scroll_to_bottom = true;
+            }
+            AppMessage::ModbusWritten {
+                address,
+                values,
+                elapsed,
+            } => {
+                self.logs.push(format!(
+                    "TX wrote {:?} at {} in {} ms",
+                    values,
+                    address,
+                    elapsed.as_millis()
+                ));
+                self.status = format!("Wrote {} value(s) at {}", values.len(), address);
+                self.scroll_to_bottom = true;
 
-            ui.label("Port");
-            ui.add(egui::DragValue::new(&mut self.tcp_port));
+                // written from the table, show what the device now holds
+                if !self.function.is_write() {
+                    self.read_now();
+                }
+            }
+            AppMessage::ModbusVerified {
+                address,
+                written,
+                read,
+                elapsed,
+            } => {
+                let outcome = if written == read {
+                    Ok(format!("read back {:?} at {}", read, address))
+                } else {
+                    let mismatched: Vec<String> = written
+                        .iter()
+                        .zip(&read)
+                        .enumerate()
+                        .filter(|(_, (w, r))| w != r)
+                        .map(|(i, (w, r))| {
+                            format!("{}: wrote {}, read {}", address as usize + i, w, r)
+                        })
+                        .collect();
+                    Err(format!("mismatch at {}", mismatched.join("; ")))
+                };
+                match &outcome {
+                    Ok(text) => {
+                        self.logs.push(format!(
+                            "VERIFY PASS wrote {:?}, {} in {} ms",
+                            written,
+                            text,
+                            elapsed.as_millis()
+                        ));
+                        self.status = format!("Verified {} value(s) at {}", written.len(), address);
+                    }
+                    Err(text) => {
+                        self.logs.push(format!(
+                            "VERIFY FAIL wrote {:?}, read {:?}: {}",
+                            written, read, text
+                        ));
+                        self.status = format!("Write verify FAILED, {}", text);
+                    }
+                }
+                self.last_verify = Some(outcome);
+                self.scroll_to_bottom = true;
+            }
+            AppMessage::ModbusRetry {
+                attempt,
+                retries,
+                delay,
+            } => {
+                self.logs.push(format!(
+                    "retry {}/{} after {}ms",
+                    attempt,
+                    retries,
+                    delay.as_millis()
+                ));
+                self.scroll_to_bottom = true;
+            }
+            AppMessage::ModbusError {
+                function,
+                address,
+                quantity,
+                kind,
+                message,
+                elapsed,
+            } => {
+                let request = self.block_title(&PollBlock {
+                    function,
+                    address,
+                    quantity,
+                });
+                // exceptions are answers from the device, keep them apart from I/O errors
+                let tag = match kind {
+                    PollFailure::Exception => "EXC",
+                    _ => "ERR",
+                };
+                self.logs.push(format!(
+                    "{} {} ({} after {} ms): {}",
+                    tag,
+                    request,
+                    kind.label(),
+                    elapsed.as_millis(),
+                    message
+                ));
+                self.status = format!("{} failed, {}: {}", request, kind.origin(), message);
+                self.scroll_to_bottom = true;
+
+                // an exception is still an answer, only silence makes the data stale
+                if self.stop_tx.is_some() && kind != PollFailure::Exception {
+                    self.poll_failures += 1;
+                    // the threshold can be lowered below the count mid-run
+                    if !self.stale_warned && self.poll_failures >= self.stale_after {
+                        self.stale_warned = true;
+                        self.status =
+                            format!("No response for {} consecutive polls", self.poll_failures);
+                        self.logs.push(format!("WARN {}", self.status));
+                    }
+                }
+            }
+            AppMessage::ModbusScanned {
+                slave,
+                failure,
+                elapsed,
+            } => {
+                self.scan_at = slave;
+                // silence is the normal case on a scan, only answers are logged
+                match &failure {
+                    None => self.logs.push(format!(
+                        "SCAN slave {} answered in {} ms",
+                        slave,
+                        elapsed.as_millis()
+                    )),
+                    Some((PollFailure::Exception, message)) => self.logs.push(format!(
+                        "SCAN slave {} answered with an exception: {}",
+                        slave, message
+                    )),
+                    Some(_) => {}
+                }
+                self.scroll_to_bottom |= scan_answered(&failure);
+                self.scan_results.push((slave, failure));
+            }
+            AppMessage::ModbusScanDone => {
+                self.scan_stop = None;
+                self.status = format!("Scan done, {}", self.scan_summary());
+                self.logs.push(format!("SCAN {}", self.status));
+                self.scroll_to_bottom = true;
+            }
+            AppMessage::ModbusSwept {
+                address,
+                quantity,
+                result,
+            } => {
+                self.sweep_done += quantity as u32;
+                match result {
+                    Ok(data) => {
+                        for (i, value) in data.into_iter().enumerate() {
+                            if value == 0 {
+                                self.sweep_zeros += 1;
+                            } else {
+                                self.sweep_values
+                                    .insert(address.wrapping_add(i as u16), value);
+                            }
+                        }
+                    }
+                    Err(e) => self.sweep_errors.push((address, quantity, e)),
+                }
+            }
+            AppMessage::ModbusSweepDone => {
+                self.sweep_stop = None;
+                self.status = format!("Sweep done, {}", self.sweep_summary());
+                self.logs.push(format!("SWEEP {}", self.status));
+                self.scroll_to_bottom = true;
+            }
+            AppMessage::ModbusFrame { sent, bytes } => self.frames.push(sent, &bytes),
+            _ => {}
+        }
+    }
+
+    // slow device vs slow network: a steady high average points at the link
+    fn ui_latency(&self, ui: &mut egui::Ui) {
+        let Some(last) = self.latencies.back() else {
+            return;
+        };
+        let avg = self.latencies.iter().sum::<Duration>() / self.latencies.len() as u32;
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Latency: ").strong());
+            ui.monospace(format!("last {} ms", last.as_millis()));
+            ui.separator();
+            ui.monospace(format!(
+                "avg {} ms over {} reads",
+                avg.as_millis(),
+                self.latencies.len()
+            ));
         });
     }
 
-    fn ui_rtu(&mut self, ui: &mut egui::Ui) {
+    fn ui_status(&mut self, ui: &mut egui::Ui) {
+        egui::TopBottomPanel::bottom("modbus_status").show(ui.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Status: ").color(egui::Color32::RED));
+                ui.monospace(&self.status);
+            });
+        });
+    }
+
+    // green connected, amber while the connect task runs, grey otherwise
+    fn ui_connection_light(&self, ui: &mut egui::Ui) {
+        let (color, text) = if self.connected {
+            (egui::Color32::from_rgb(40, 180, 70), "Connected")
+        } else if self.link.is_some() {
+            // nothing else repaints until the task reports back
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+            (egui::Color32::from_rgb(230, 160, 20), "Connecting...")
+        } else {
+            (egui::Color32::GRAY, "Disconnected")
+        };
+        ui.label(egui::RichText::new(format!("● {}", text)).color(color));
+    }
+
+    fn ui_connection(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Connection").strong());
+                self.ui_connection_light(ui);
+            });
+
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(self.link.is_none(), |ui| {
+                    ui.selectable_value(&mut self.mode, ModbusMode::Tcp, "TCP");
+                    ui.selectable_value(&mut self.mode, ModbusMode::Tls, "TLS");
+                    ui.selectable_value(&mut self.mode, ModbusMode::Rtu, "RTU");
+                    ui.selectable_value(&mut self.mode, ModbusMode::RtuOverTcp, "RTU over TCP");
+                });
+
+                ui.separator();
+
+                ui.add_enabled_ui(self.link.is_none(), |ui| {
+                    ui.label("Retries");
+                    ui.add(egui::DragValue::new(&mut self.retries).range(0..=10));
+
+                    ui.label("Backoff");
+                    ui.add(
+                        egui::DragValue::new(&mut self.retry_delay_ms)
+                            .range(10..=10000)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text("Delay before the first retry, doubled for each further one");
+                });
+            });
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        self.link.is_none(),
+                        egui::Button::new(
+                            egui::RichText::new("Connect").color(egui::Color32::BLUE),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.connect();
+                }
+                if ui
+                    .add_enabled(
+                        self.link.is_some(),
+                        egui::Button::new(
+                            egui::RichText::new("Disconnect").color(egui::Color32::RED),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.disconnect();
+                }
+            });
+
+            ui.separator();
+
+            // the session was opened with these settings, disconnect to change them
+            ui.add_enabled_ui(self.link.is_none(), |ui| match self.mode {
+                ModbusMode::Tcp | ModbusMode::RtuOverTcp => self.ui_tcp(ui),
+                ModbusMode::Tls => self.ui_tls(ui),
+                ModbusMode::Rtu => self.ui_rtu(ui),
+            });
+
+            #[cfg(feature = "link-sim")]
+            self.link_sim.lock().unwrap().ui(ui);
+        });
+    }
+
+    fn ui_tcp(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
+            ui.label("IP");
+            ui.text_edit_singleline(&mut self.tcp_ip);
+
             ui.label("Port");
-            egui::ComboBox::from_id_salt("rtu_port")
-                .selected_text(
-                    self.selected_port
-                        .clone()
-                        .unwrap_or_else(|| "Select".into()),
+            ui.add(egui::DragValue::new(&mut self.tcp_port));
+
+            self.ui_timeout(ui);
+        });
+    }
+
+    fn ui_timeout(&mut self, ui: &mut egui::Ui) {
+        ui.label("Timeout");
+        ui.add(
+            egui::DragValue::new(&mut self.timeout_ms)
+                .range(100..=60000)
+                .suffix(" ms"),
+        )
+        .on_hover_text("Limit for connecting and for each request");
+    }
+
+    fn ui_tls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("IP");
+            ui.text_edit_singleline(&mut self.tcp_ip);
+
+            ui.label("Port");
+            ui.add(egui::DragValue::new(&mut self.tls_port));
+
+            self.ui_timeout(ui);
+
+            ui.checkbox(&mut self.tls.insecure, "Skip verify")
+                .on_hover_text("Accept any server certificate (lab use only)");
+        });
+
+        egui::Grid::new("tls_files").show(ui, |ui| {
+            ui.label("CA cert");
+            ui.add_enabled(
+                !self.tls.insecure,
+                egui::TextEdit::singleline(&mut self.tls.ca_path).hint_text("ca.pem"),
+            );
+            ui.end_row();
+
+            ui.label("Client cert");
+            ui.add(egui::TextEdit::singleline(&mut self.tls.cert_path).hint_text("client.pem"));
+            ui.end_row();
+
+            ui.label("Client key");
+            ui.add(egui::TextEdit::singleline(&mut self.tls.key_path).hint_text("client.key"));
+            ui.end_row();
+        });
+    }
+
+    fn config(&self) -> ModbusConfig {
+        ModbusConfig {
+            mode: self.mode,
+            tcp_ip: self.tcp_ip.clone(),
+            tcp_port: self.tcp_port,
+            tls_port: self.tls_port,
+            rtu_port: self.port_settings.selected_port.clone(),
+            baud_rate: self.port_settings.baud_rate,
+            data_bits: self.port_settings.data_bits,
+            parity: self.port_settings.parity,
+            stop_bits: self.port_settings.stop_bits,
+            flow_control: self.port_settings.flow_control,
+            retries: self.retries,
+            retry_delay_ms: self.retry_delay_ms,
+            timeout_ms: self.timeout_ms,
+
+            slave_id: self.slave_id,
+            function: self.function,
+            address: self.address,
+            quantity: self.quantity,
+            blocks: self.blocks.clone(),
+
+            view_rows: self.view_rows,
+            display_format: self.display_format.into(),
+            byte_order: self.byte_order,
+            decimals: self.decimals,
+            poll_interval_ms: self.poll_interval_ms,
+            stale_after: self.stale_after,
+        }
+    }
+
+    fn apply_config(&mut self, config: ModbusConfig) {
+        self.stop_auto_poll();
+
+        self.mode = config.mode;
+        self.tcp_ip = config.tcp_ip;
+        self.tcp_port = config.tcp_port;
+        self.tls_port = config.tls_port;
+        self.port_settings.selected_port = config.rtu_port;
+        self.port_settings.baud_rate = config.baud_rate;
+        self.port_settings.data_bits = config.data_bits;
+        self.port_settings.parity = config.parity;
+        self.port_settings.stop_bits = config.stop_bits;
+        self.port_settings.flow_control = config.flow_control;
+        self.retries = config.retries;
+        self.retry_delay_ms = config.retry_delay_ms;
+        self.timeout_ms = config.timeout_ms;
+
+        self.slave_id = config.slave_id;
+        self.function = config.function;
+        self.address = config.address;
+        self.quantity = config.quantity;
+        self.blocks = config.blocks;
+        self.block_data.clear();
+        for _ in 0..self.blocks.len() {
+            let data = self.new_block_data();
+            self.block_data.push(data);
+        }
+        self.editing = None;
+
+        self.view_rows = config.view_rows;
+        self.display_format = config.display_format.format;
+        self.byte_order = config.byte_order;
+        self.decimals = config.decimals;
+        self.poll_interval_ms = config.poll_interval_ms;
+        self.stale_after = config.stale_after;
+        self.poll_interval
+            .store(self.poll_interval_ms, Ordering::Relaxed);
+    }
+
+    fn connect(&mut self) {
+        if self.link.is_some() {
+            return;
+        }
+        let endpoint = match self.endpoint() {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                self.status = e;
+                return;
+            }
+        };
+        let Some(rt) = &self.rt else {
+            return;
+        };
+
+        let link = Link::new(endpoint)
+            .with_retry(RetryPolicy {
+                retries: self.retries,
+                base_delay: Duration::from_millis(self.retry_delay_ms),
+            })
+            .with_timeout(Duration::from_millis(self.timeout_ms))
+            .with_frame_tap(self.frames.tap(self.tx.clone()));
+        #[cfg(feature = "link-sim")]
+        let link = link.with_sim(self.link_sim.clone());
+        self.link = Some(link.clone());
+        self.status = "Connecting...".into();
+
+        let tx = self.tx.clone();
+        rt.spawn(async move {
+            let result = link.open().await.map_err(|e| e.to_string());
+            let _ = tx.send(AppMessage::ModbusConnected(result));
+        });
+    }
+
+    pub fn disconnect(&mut self) {
+        self.stop_auto_poll();
+        self.stop_scan();
+        self.stop_sweep();
+
+        let Some(link) = self.link.take() else {
+            return;
+        };
+        if let Some(rt) = &self.rt {
+            rt.spawn(async move { link.close().await });
+        }
+
+        self.connected = false;
+        self.latencies.clear();
+        self.pending_write = None;
+        self.status = "Disconnected".into();
+        self.logs.push("Disconnected".into());
+        self.scroll_to_bottom = true;
+    }
+
+    // reads and writes only go out over an established session
+    fn link(&self) -> Result<Link, String> {
+        match &self.link {
+            Some(link) if self.connected => Ok(link.clone()),
+            _ => Err("Not connected".into()),
+        }
+    }
+
+    fn endpoint(&self) -> Result<Endpoint, String> {
+        match self.mode {
+            ModbusMode::Tcp => Ok(Endpoint::Tcp {
+                ip: self.tcp_ip.clone(),
+                port: self.tcp_port,
+            }),
+            ModbusMode::RtuOverTcp => Ok(Endpoint::RtuOverTcp {
+                ip: self.tcp_ip.clone(),
+                port: self.tcp_port,
+            }),
+            ModbusMode::Tls => Ok(Endpoint::Tls {
+                ip: self.tcp_ip.clone(),
+                port: self.tls_port,
+                tls: self.tls.clone(),
+            }),
+            ModbusMode::Rtu => match &self.port_settings.selected_port {
+                Some(port) => Ok(Endpoint::Rtu {
+                    builder: self.port_settings.builder(port),
+                }),
+                None => Err("No serial port selected".into()),
+            },
+        }
+    }
+
+    fn ui_rtu(&mut self, ui: &mut egui::Ui) {
+        serial_settings_ui(ui, "modbus_rtu", &mut self.port_settings);
+
+        ui.horizontal(|ui| self.ui_timeout(ui));
+    }
+
+    fn ui_slave(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(egui::RichText::new("Slave").strong());
+
+            let before = (self.slave_id, self.function, self.address, self.quantity);
+
+            ui.horizontal(|ui| {
+                ui.label("Slave ID");
+                ui.add(egui::DragValue::new(&mut self.slave_id).range(1..=247));
+
+                ui.label("Function");
+                egui::ComboBox::from_id_salt("func")
+                    .selected_text(format!("{:?}", self.function))
+                    .show_ui(ui, |ui| {
+                        for function in ModbusFunction::ALL {
+                            ui.selectable_value(&mut self.function, function, function.label());
+                        }
+                    });
+
+                ui.label("Address");
+                ui.add(egui::DragValue::new(&mut self.address));
+
+                if !self.function.is_write() {
+                    ui.label("Quantity");
+                    ui.add(
+                        egui::DragValue::new(&mut self.quantity)
+                            .range(1..=self.function.max_quantity()),
+                    );
+                }
+
+                ui.separator();
+
+                if ui
+                    .checkbox(&mut self.auto_read, "Auto-read on change")
+                    .on_hover_text("Read once parameter edits have settled for 300 ms")
+                    .changed()
+                {
+                    self.auto_read_due = None;
+                }
+            });
+
+            if self.function.is_write() {
+                ui.horizontal(|ui| {
+                    ui.label("Values");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.write_values)
+                            .hint_text(if self.function.is_coil() {
+                                "1, 0, 1"
+                            } else {
+                                "100, -2, 0x1F"
+                            })
+                            .desired_width(300.0),
+                    );
+                    if ui
+                        .add_enabled(
+                            self.connected,
+                            egui::Button::new(
+                                egui::RichText::new("Write").color(egui::Color32::BLUE),
+                            ),
+                        )
+                        .on_disabled_hover_text("Connect first")
+                        .clicked()
+                    {
+                        self.exec_once();
+                    }
+                    if ui
+                        .add_enabled(self.connected, egui::Button::new("Write + Verify"))
+                        .on_hover_text("Write, then read the same range back and compare")
+                        .on_disabled_hover_text("Connect first")
+                        .clicked()
+                    {
+                        self.write_verify();
+                    }
+
+                    match &self.last_verify {
+                        Some(Ok(text)) => {
+                            ui.colored_label(egui::Color32::DARK_GREEN, format!("✔ {}", text));
+                        }
+                        Some(Err(text)) => {
+                            ui.label(
+                                egui::RichText::new(format!("✖ {}", text))
+                                    .strong()
+                                    .color(ui.visuals().error_fg_color),
+                            );
+                        }
+                        None => {}
+                    }
+                });
+            }
+
+            let blocks_before = self.blocks.clone();
+            self.ui_blocks(ui);
+            // an edited block's old values, and reads still in flight,
+            // belong to another range
+            if self.blocks.len() == blocks_before.len() {
+                let edited: Vec<usize> = (0..self.blocks.len())
+                    .filter(|&i| self.blocks[i] != blocks_before[i])
+                    .collect();
+                for i in edited {
+                    self.block_data[i] = self.new_block_data();
+                }
+            }
+
+            let after = (self.slave_id, self.function, self.address, self.quantity);
+            if (before.1, before.2) != (after.1, after.2) {
+                self.trend.clear();
+            }
+            let changed = before != after || self.blocks != blocks_before;
+            if self.auto_read && changed && !self.function.is_write() {
+                self.auto_read_due = Some(Instant::now() + AUTO_READ_DEBOUNCE);
+            }
+        });
+    }
+
+    fn ui_blocks(&mut self, ui: &mut egui::Ui) {
+        let mut remove = None;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Blocks");
+            for (i, block) in self.blocks.iter_mut().enumerate() {
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt(("block_func", i))
+                            .selected_text(block.function.label())
+                            .show_ui(ui, |ui| {
+                                for function in ModbusFunction::ALL {
+                                    if !function.is_write() {
+                                        ui.selectable_value(
+                                            &mut block.function,
+                                            function,
+                                            function.label(),
+                                        );
+                                    }
+                                }
+                            });
+                        ui.add(egui::DragValue::new(&mut block.address));
+                        ui.label("x");
+                        ui.add(
+                            egui::DragValue::new(&mut block.quantity)
+                                .range(1..=block.function.max_quantity()),
+                        );
+                        if ui.small_button("✖").on_hover_text("Remove block").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                });
+            }
+
+            if ui
+                .button("+ Block")
+                .on_hover_text("Also read another address range, shown as its own table section")
+                .clicked()
+            {
+                // continue after the last range, with the same kind of read
+                let (function, end) = match self.blocks.last() {
+                    Some(last) => (last.function, last.address.saturating_add(last.quantity)),
+                    None if self.function.is_write() => (ModbusFunction::ReadHolding, 0),
+                    None => (self.function, self.address.saturating_add(self.quantity)),
+                };
+                self.blocks.push(PollBlock {
+                    function,
+                    address: end,
+                    quantity: 10,
+                });
+                let data = self.new_block_data();
+                self.block_data.push(data);
+            }
+        });
+
+        if let Some(i) = remove {
+            self.blocks.remove(i);
+            self.block_data.remove(i);
+        }
+    }
+
+    fn new_block_data(&mut self) -> BlockData {
+        self.next_block_id += 1;
+        BlockData {
+            id: self.next_block_id,
+            data: Vec::new(),
+        }
+    }
+
+    // the id results of the main read (None) or block `i` are tagged with
+    fn block_id(&self, block: Option<usize>) -> Option<u64> {
+        block.map(|i| self.block_data[i].id)
+    }
+
+    // fires the debounced read; while polling the poll is restarted with the new parameters
+    fn auto_read_tick(&mut self, ctx: &egui::Context) {
+        let Some(due) = self.auto_read_due else {
+            return;
+        };
+
+        let now = Instant::now();
+        if now < due {
+            ctx.request_repaint_after(due - now);
+            return;
+        }
+
+        self.auto_read_due = None;
+        self.read_now();
+    }
+
+    // while polling the poll is restarted with the current parameters;
+    // never fires a write on its own
+    fn read_now(&mut self) {
+        if self.function.is_write() {
+            return;
+        }
+        if self.stop_tx.is_some() {
+            self.stop_auto_poll();
+            self.start_auto_poll();
+        } else {
+            self.exec_once();
+        }
+    }
+
+    fn ui_favorites(&mut self, ui: &mut egui::Ui) {
+        let mut jump = None;
+        let mut remove = None;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(egui::RichText::new("Favorites: ").strong());
+
+            for (i, fav) in self.favorites.items.iter().enumerate() {
+                let button = ui.button(&fav.name).on_hover_text(format!(
+                    "{:?} @ {} x{}",
+                    fav.function, fav.address, fav.quantity
+                ));
+                if button.clicked() {
+                    jump = Some(fav.clone());
+                }
+                button.context_menu(|ui| {
+                    if ui.button("Remove").clicked() {
+                        remove = Some(i);
+                        ui.close();
+                    }
+                });
+            }
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.favorite_name)
+                    .hint_text("name")
+                    .desired_width(100.0),
+            );
+            if ui
+                .add_enabled(
+                    !self.favorite_name.trim().is_empty(),
+                    egui::Button::new("☆ Add"),
                 )
-                .show_ui(ui, |ui| {
-                    for p in &self.available_ports {
-                        ui.selectable_value(&mut self.selected_port, Some(p.clone()), p);
+                .on_hover_text("Bookmark the current function / address / quantity")
+                .clicked()
+            {
+                self.favorites.items.push(Favorite {
+                    name: self.favorite_name.trim().to_string(),
+                    function: self.function,
+                    address: self.address,
+                    quantity: self.quantity,
+                });
+                self.favorite_name.clear();
+                self.save_favorites();
+            }
+        });
+
+        if let Some(i) = remove {
+            self.favorites.items.remove(i);
+            self.save_favorites();
+        }
+
+        self.ui_favorites_error(ui);
+
+        if let Some(fav) = jump {
+            self.function = fav.function;
+            self.address = fav.address;
+            self.quantity = fav.quantity;
+            self.trend.clear();
+            self.auto_read_due = None;
+            self.read_now();
+        }
+    }
+
+    // values assembled from non-adjacent registers, defined in the register map
+    fn ui_mapped(&self, ui: &mut egui::Ui) {
+        if self.register_map.mapped.is_empty() {
+            return;
+        }
+
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            egui::Grid::new("modbus_mapped")
+                .striped(true)
+                .min_col_width(80.0)
+                .show(ui, |ui| {
+                    for value in &self.register_map.mapped {
+                        ui.label(&value.name);
+                        ui.label(
+                            value
+                                .words
+                                .iter()
+                                .map(|a| {
+                                    self.address_notation.format(
+                                        self.function,
+                                        *a,
+                                        self.register_map.display_offset,
+                                    )
+                                })
+                                .collect::<Vec<_>>()
+                                .join(" + "),
+                        );
+                        match value.decode(self.address, &self.data, self.decimals) {
+                            Some(v) => ui.monospace(v),
+                            None => ui
+                                .label(egui::RichText::new("-").weak())
+                                .on_hover_text("Not all source registers are in the read range"),
+                        };
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn ui_favorites_error(&mut self, ui: &mut egui::Ui) {
+        let Some(error) = &self.favorites.load_error else {
+            return;
+        };
+
+        let mut reload = false;
+        let mut overwrite = false;
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(ui.visuals().error_fg_color, format!("✖ {}", error));
+            reload = ui
+                .button("Reload")
+                .on_hover_text("Read the file again, e.g. after fixing it by hand")
+                .clicked();
+            overwrite = ui
+                .button("Overwrite")
+                .on_hover_text("Replace the file with the favorites shown here")
+                .clicked();
+        });
+
+        if reload {
+            match Favorites::load(FAVORITES_FILE) {
+                Ok(favorites) => {
+                    self.favorites = favorites;
+                    self.status = format!("Loaded {}", FAVORITES_FILE);
+                }
+                Err(e) => {
+                    let error = format!("Load {} failed: {}", FAVORITES_FILE, e);
+                    self.status = error.clone();
+                    self.favorites.load_error = Some(error);
+                }
+            }
+        }
+        if overwrite {
+            self.favorites.load_error = None;
+            self.save_favorites();
+        }
+    }
+
+    fn save_favorites(&mut self) {
+        if let Err(e) = self.favorites.save(FAVORITES_FILE) {
+            self.status = format!("Save failed: {}", e);
+        }
+    }
+
+    fn ui_view(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(egui::RichText::new("View").strong());
+
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Row: ").strong());
+                ui.radio_value(&mut self.view_rows, 10, "10");
+                ui.radio_value(&mut self.view_rows, 20, "20");
+
+                ui.label(egui::RichText::new("Address: ").strong());
+                ui.radio_value(
+                    &mut self.address_notation,
+                    AddressNotation::Protocol,
+                    "Protocol",
+                );
+                ui.radio_value(&mut self.address_notation, AddressNotation::Plc, "PLC");
+
+                ui.label("Offset");
+                let offset = ui
+                    .add(egui::DragValue::new(&mut self.register_map.display_offset))
+                    .on_hover_text(
+                        "Added to the shown address only, to match the device documentation",
+                    );
+                if offset.drag_stopped() || (offset.changed() && !offset.dragged()) {
+                    if let Err(e) = self.register_map.save(REGISTER_MAP_FILE) {
+                        self.status = format!("Save failed: {}", e);
+                    }
+                }
+
+                ui.label(egui::RichText::new("Display: ").strong());
+                egui::ComboBox::from_id_salt("display")
+                    .selected_text(self.display_format.label())
+                    .show_ui(ui, |ui: &mut egui::Ui| {
+                        for format in DisplayFormat::ALL {
+                            ui.selectable_value(&mut self.display_format, format, format.label());
+                        }
+                    });
+
+                ui.label("Order");
+                egui::ComboBox::from_id_salt("byte_order")
+                    .selected_text(self.byte_order.label())
+                    .show_ui(ui, |ui| {
+                        for order in ByteOrder::ALL {
+                            ui.selectable_value(&mut self.byte_order, order, order.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Byte order of multi-register values, A = most significant");
+
+                ui.label("Decimals");
+                ui.add(egui::DragValue::new(&mut self.decimals).range(0..=MAX_DECIMALS))
+                    .on_hover_text("Digits after the point for Float and Double");
+
+                if self.display_format.is_binary() {
+                    ui.checkbox(&mut self.binary_grouped, "Group nibbles");
+                    ui.checkbox(&mut self.binary_indices, "Bit indices");
+                }
+            });
+        });
+
+        ui.horizontal(|ui: &mut egui::Ui| {
+            let running = self.stop_tx.is_some();
+
+            ui.label("Interval");
+            if ui
+                .add(
+                    egui::DragValue::new(&mut self.poll_interval_ms)
+                        .range(50..=60000)
+                        .suffix(" ms"),
+                )
+                .changed()
+            {
+                self.poll_interval
+                    .store(self.poll_interval_ms, Ordering::Relaxed);
+            }
+
+            ui.label("Stale after");
+            ui.add(
+                egui::DragValue::new(&mut self.stale_after)
+                    .range(1..=100)
+                    .suffix(" fails"),
+            )
+            .on_hover_text("Warn and grey out the table after this many polls without an answer");
+
+            if !running {
+                let reads = self.connected && !self.function.is_write();
+                if ui
+                    .add_enabled(reads, egui::Button::new("Read Once"))
+                    .on_disabled_hover_text("Connect and pick a read function first")
+                    .clicked()
+                {
+                    self.exec_once();
+                }
+                if ui
+                    .add_enabled(
+                        reads,
+                        egui::Button::new(
+                            egui::RichText::new(format!(
+                                "▶ Start Auto Poll ({} ms)",
+                                self.poll_interval_ms
+                            ))
+                            .color(egui::Color32::BLUE),
+                        ),
+                    )
+                    .on_hover_text("Space over the table toggles polling")
+                    .on_disabled_hover_text("Connect and pick a read function first")
+                    .clicked()
+                {
+                    self.start_auto_poll();
+                }
+            } else {
+                if ui
+                    .button(
+                        egui::RichText::new(format!(
+                            "⏹ Stop Auto Poll ({} ms)",
+                            self.poll_interval_ms
+                        ))
+                        .color(egui::Color32::RED),
+                    )
+                    .on_hover_text("Space over the table toggles polling")
+                    .clicked()
+                {
+                    self.stop_auto_poll();
+                }
+            }
+
+            ui.separator();
+
+            if ui.button("Register Map…").clicked() {
+                self.map_editor.open = true;
+            }
+            if let Some(error) = &self.register_map.load_error {
+                ui.colored_label(ui.visuals().error_fg_color, "✖ not loaded")
+                    .on_hover_text(format!(
+                        "{}\nOpen the Register Map to reload or overwrite it",
+                        error
+                    ));
+            }
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.config_path)
+                    .hint_text(CONFIG_FILE)
+                    .desired_width(160.0),
+            );
+            if ui.button("Save Config").clicked() {
+                self.status = match self.config().save(&self.config_path) {
+                    Ok(_) => format!("Saved {}", self.config_path),
+                    Err(e) => format!("Save failed: {}", e),
+                };
+            }
+            if ui
+                .add_enabled(self.link.is_none(), egui::Button::new("Load Config"))
+                .on_disabled_hover_text("Disconnect first")
+                .clicked()
+            {
+                match ModbusConfig::load(&self.config_path) {
+                    Ok(config) => {
+                        self.apply_config(config);
+                        self.status = format!("Loaded {}", self.config_path);
+                    }
+                    Err(e) => self.status = format!("Load failed: {}", e),
+                }
+            }
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.export_path)
+                    .hint_text(SNAPSHOT_FILE)
+                    .desired_width(160.0),
+            );
+            if ui
+                .button("Export JSON")
+                .on_hover_text("Write the table as shown, with raw registers, to this file")
+                .clicked()
+            {
+                self.status = match self.snapshot().save(&self.export_path) {
+                    Ok(_) => format!("Exported {}", self.export_path),
+                    Err(e) => format!("Export failed: {}", e),
+                };
+            }
+        });
+    }
+
+    // every row that has data, decoded the way the table shows it
+    fn snapshot(&self) -> Snapshot {
+        let registers = self
+            .build_sections()
+            .into_iter()
+            .flat_map(|(_, rows)| rows)
+            .filter(|row| !row.raw.is_empty())
+            .map(|row| {
+                let bits = self.read_of(row.block).0.function.reads_bits();
+                SnapshotRow {
+                    addr: row.address,
+                    name: Some(row.name).filter(|name| !name.is_empty()),
+                    unit: if bits || row.format.is_binary() {
+                        None
+                    } else {
+                        self.register_map.unit(row.address).map(str::to_string)
+                    },
+                    raw_registers: row.raw,
+                    decoded_value: row.lookup.unwrap_or(row.plain),
+                    format: row.format,
+                }
+            })
+            .collect();
+
+        Snapshot {
+            taken_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            mode: self.mode,
+            endpoint: match self.mode {
+                ModbusMode::Tcp | ModbusMode::RtuOverTcp => {
+                    format!("{}:{}", self.tcp_ip, self.tcp_port)
+                }
+                ModbusMode::Tls => format!("{}:{}", self.tcp_ip, self.tls_port),
+                ModbusMode::Rtu => format!(
+                    "{} @ {}",
+                    self.port_settings.selected_port.as_deref().unwrap_or("-"),
+                    self.port_settings.baud_rate
+                ),
+            },
+            slave_id: self.slave_id,
+            function: self.function,
+            address: self.address,
+            quantity: self.quantity,
+            byte_order: self.byte_order,
+            registers,
+        }
+    }
+
+    pub fn ui_table(&mut self, ui: &mut egui::Ui, sections: &mut [(String, Vec<ModbusRow>)]) {
+        let table = egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new("modbus_table")
+                    .striped(true)
+                    .min_col_width(80.0)
+                    .show(ui, |ui| {
+                        ui.label("Index");
+                        ui.label("Address");
+                        ui.label("Name");
+                        ui.label("Raw");
+                        ui.horizontal(|ui| {
+                            ui.label("Value");
+                            if ui
+                                .small_button(self.byte_order.label())
+                                .on_hover_text("Cycle byte order (ABCD → DCBA → BADC → CDAB)")
+                                .clicked()
+                            {
+                                self.byte_order = self.byte_order.next();
+                            }
+                        });
+                        ui.end_row();
+
+                        let mut reformat = None;
+                        let mut renamed = None;
+                        let mut rescale = None;
+                        let mut commit = None;
+                        let mut toggle = None;
+                        for (title, rows) in sections.iter_mut() {
+                            if !title.is_empty() {
+                                ui.label(egui::RichText::new(title.as_str()).strong());
+                                ui.end_row();
+                            }
+                            for row in rows.iter_mut() {
+                                let function = self.read_of(row.block).0.function;
+                                ui.label(row.index.to_string());
+                                ui.label(self.address_notation.format(
+                                    function,
+                                    row.address,
+                                    self.register_map.display_offset,
+                                ));
+
+                                // inline edit, saved to the register map when the field loses focus
+                                let name = ui.add(
+                                    egui::TextEdit::singleline(&mut row.name)
+                                        .hint_text("-")
+                                        .desired_width(100.0),
+                                );
+                                if name.changed() || name.lost_focus() {
+                                    renamed =
+                                        Some((row.address, row.name.clone(), name.lost_focus()));
+                                }
+
+                                // past the end of the last read: "-", never a made-up 0
+                                let unread = row.raw.is_empty();
+                                if unread {
+                                    ui.weak("-");
+                                } else if function.reads_bits() {
+                                    ui.label(row.raw[0].to_string());
+                                } else {
+                                    ui.label(
+                                        row.raw
+                                            .iter()
+                                            .map(|v| format!("{:04X}", v))
+                                            .collect::<Vec<_>>()
+                                            .join(" "),
+                                    )
+                                    .on_hover_ui(|ui| self.ui_inspector(ui, &row.raw));
+                                }
+
+                                // holding registers can be written back from the table
+                                let editable = function == ModbusFunction::ReadHolding
+                                    && self.connected
+                                    && row.format.is_editable()
+                                    && row.raw.len() == row.format.register_count();
+                                // coils flip with a click, discrete inputs are read-only
+                                let toggleable = function == ModbusFunction::ReadCoils
+                                    && self.connected
+                                    && !unread;
+
+                                let block_id = self.block_id(row.block);
+                                let editing = self.editing.as_mut().filter(|(block, addr, _)| {
+                                    *block == block_id && *addr == row.address
+                                });
+                                let was_editing = editing.is_some();
+                                let value = if let Some((_, _, text)) = editing {
+                                    let edit = ui.add(
+                                        egui::TextEdit::singleline(text)
+                                            .desired_width(120.0)
+                                            .hint_text("Enter to write, Esc to cancel"),
+                                    );
+                                    if edit.lost_focus() {
+                                        if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                                            commit = Some((row.address, row.format, text.clone()));
+                                        }
+                                        self.editing = None;
+                                    } else if !edit.has_focus() {
+                                        edit.request_focus();
+                                    }
+                                    edit
+                                } else if let Some(label) = &row.lookup {
+                                    ui.label(label).on_hover_text(format!("Raw: {}", row.value))
+                                } else if unread {
+                                    ui.weak(&row.value)
+                                        .on_hover_text("Not covered by the last read")
+                                } else if row.format.is_binary() && self.binary_indices {
+                                    let ruler = bit_ruler(
+                                        row.format.register_count() * 16,
+                                        self.binary_grouped,
+                                    );
+                                    ui.vertical(|ui| {
+                                        ui.label(
+                                            egui::RichText::new(ruler).monospace().small().weak(),
+                                        );
+                                        ui.monospace(&row.value);
+                                    })
+                                    .response
+                                } else if let (true, Some(raw)) = (row.valid, &row.unscaled) {
+                                    ui.label(&row.value).on_hover_text(format!(
+                                        "Raw: {} (× {} + {})",
+                                        raw, row.scale, row.offset
+                                    ))
+                                } else if row.valid {
+                                    ui.label(&row.value)
+                                } else {
+                                    ui.label(egui::RichText::new(&row.value).weak())
+                                        .on_hover_text(format!(
+                                        "Not a finite value (raw {}) in {} order, try another byte order",
+                                        row.raw
+                                            .iter()
+                                            .map(|v| format!("{:04X}", v))
+                                            .collect::<Vec<_>>()
+                                            .join(" "),
+                                        self.byte_order.label()
+                                    ))
+                                };
+                                if editable
+                                    && !was_editing
+                                    && value
+                                        .interact(egui::Sense::click())
+                                        .on_hover_text("Click to write a new value")
+                                        .clicked()
+                                {
+                                    self.editing =
+                                        Some((block_id, row.address, row.plain.clone()));
+                                }
+                                if toggleable
+                                    && value
+                                        .interact(egui::Sense::click())
+                                        .on_hover_text("Click to switch the coil")
+                                        .clicked()
+                                {
+                                    toggle = Some((row.address, row.raw[0] == 0));
+                                }
+                                value.context_menu(|ui| {
+                                    if let Some(format) = self.ui_format_menu(ui, row) {
+                                        reformat = Some((row.address, format));
+                                    }
+                                    ui.menu_button("Scale / offset", |ui| {
+                                        ui.horizontal(|ui| {
+                                            ui.label("×");
+                                            let scale = ui.add(
+                                                egui::DragValue::new(&mut row.scale).speed(0.01),
+                                            );
+                                            ui.label("+");
+                                            let offset = ui.add(
+                                                egui::DragValue::new(&mut row.offset).speed(0.1),
+                                            );
+
+                                            let edit = scale.union(offset);
+                                            if edit.changed() || edit.drag_stopped() {
+                                                let done = edit.drag_stopped()
+                                                    || (edit.changed() && !edit.dragged());
+                                                rescale = Some((
+                                                    row.address,
+                                                    row.scale,
+                                                    row.offset,
+                                                    done,
+                                                ));
+                                            }
+                                        });
+                                        if ui.button("Reset").clicked() {
+                                            rescale = Some((row.address, 1.0, 0.0, true));
+                                        }
+                                    });
+                                    ui.separator();
+                                    self.ui_copy_menu(ui, row);
+                                });
+                                ui.end_row();
+                            }
+                        }
+
+                        if let Some((addr, name, done)) = renamed {
+                            self.register_map.entry(addr).name =
+                                if done { name.trim().to_string() } else { name };
+                            self.register_map.prune();
+                            if done {
+                                if let Err(e) = self.register_map.save(REGISTER_MAP_FILE) {
+                                    self.status = format!("Save failed: {}", e);
+                                }
+                            }
+                        }
+
+                        if let Some((addr, scale, offset, done)) = rescale {
+                            let entry = self.register_map.entry(addr);
+                            entry.scale = scale;
+                            entry.offset = offset;
+                            self.register_map.prune();
+                            if done {
+                                if let Err(e) = self.register_map.save(REGISTER_MAP_FILE) {
+                                    self.status = format!("Save failed: {}", e);
+                                }
+                            }
+                        }
+
+                        if let Some((addr, format, text)) = commit {
+                            self.write_cell(addr, format, &text);
+                        }
+
+                        if let Some((addr, on)) = toggle {
+                            self.write_coil(addr, on);
+                        }
+
+                        match reformat {
+                            Some((addr, Some(format))) => {
+                                self.row_formats.insert(addr, format);
+                            }
+                            Some((addr, None)) => {
+                                self.row_formats.remove(&addr);
+                            }
+                            None => {}
+                        }
+                    });
+            });
+
+        // Space over the table freezes / unfreezes live data, unless a text field has focus
+        let typing = ui.ctx().memory(|m| m.focused().is_some());
+        if !typing
+            && ui.rect_contains_pointer(table.inner_rect)
+            && ui
+                .ctx()
+                .input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Space))
+        {
+            self.toggle_auto_poll();
+        }
+    }
+
+    // the raw cell's tooltip: the registers in every basic reading at once
+    fn ui_inspector(&self, ui: &mut egui::Ui, raw: &[u16]) {
+        egui::Grid::new("modbus_inspector").show(ui, |ui| {
+            for (label, value) in inspect(raw, self.byte_order, self.decimals) {
+                ui.label(label);
+                ui.monospace(value);
+                ui.end_row();
+            }
+        });
+        if raw.len() > 1 {
+            ui.weak(format!("{} byte order", self.byte_order.label()));
+        }
+    }
+
+    fn toggle_auto_poll(&mut self) {
+        if self.stop_tx.is_some() {
+            self.stop_auto_poll();
+            self.status = "Auto Poll paused (Space to resume)".into();
+        } else {
+            self.start_auto_poll();
+        }
+    }
+
+    // Some(None) puts the row back on the table format
+    fn ui_format_menu(&self, ui: &mut egui::Ui, row: &ModbusRow) -> Option<Option<DisplayFormat>> {
+        let mut picked = None;
+        ui.menu_button("Display as", |ui| {
+            for format in DisplayFormat::ALL {
+                if ui.radio(row.format == format, format.label()).clicked() {
+                    picked = Some(Some(format));
+                    ui.close();
+                }
+            }
+            ui.separator();
+            let custom = self.row_formats.contains_key(&row.address);
+            if ui
+                .add_enabled(custom, egui::Button::new("Table format"))
+                .clicked()
+            {
+                picked = Some(None);
+                ui.close();
+            }
+        });
+        picked
+    }
+
+    // every interpretation of the registers under this cell, whatever the table shows
+    fn ui_copy_menu(&self, ui: &mut egui::Ui, row: &ModbusRow) {
+        let (read, data) = self.read_of(row.block);
+        let start = row.address.wrapping_sub(read.address) as usize;
+        let regs = data.get(start..).unwrap_or(&[]);
+
+        if ui.button("Copy raw").clicked() {
+            ui.ctx().copy_text(
+                row.raw
+                    .iter()
+                    .map(|v| format!("{:04X}", v))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+            ui.close();
+        }
+
+        ui.separator();
+
+        for format in DisplayFormat::ALL {
+            let text = format.format(regs, self.byte_order, self.decimals);
+            let enough = regs.len() >= format.register_count();
+
+            if ui
+                .add_enabled(
+                    enough,
+                    egui::Button::new(format!("Copy as {}", format.label())),
+                )
+                .on_hover_text(&text)
+                .clicked()
+            {
+                ui.ctx().copy_text(text);
+                ui.close();
+            }
+        }
+    }
+
+    fn ui_logs(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .stick_to_bottom(self.scroll_to_bottom)
+                .show(ui, |ui| {
+                    for log in &self.logs {
+                        ui.label(log);
+                    }
+                });
+
+            self.scroll_to_bottom = false;
+        });
+    }
+
+    fn start_auto_poll(&mut self) {
+        if self.stop_tx.is_some() || self.rt.is_none() {
+            return;
+        }
+        if self.function.is_write() {
+            self.status = "Auto Poll only reads, pick a read function".into();
+            return;
+        }
+
+        let link = match self.link() {
+            Ok(link) => link,
+            Err(e) => {
+                self.status = e;
+                return;
+            }
+        };
+
+        // the main range first, then the blocks in order, every cycle
+        let reads: Vec<(Option<u64>, PollBlock)> = std::iter::once((None, self.main_block()))
+            .chain(
+                self.block_data
+                    .iter()
+                    .zip(&self.blocks)
+                    .map(|(slot, &b)| (Some(slot.id), b)),
+            )
+            .collect();
+        if !reads.iter().all(|(_, read)| self.quantity_ok(read)) {
+            return;
+        }
+
+        let msg_tx = self.tx.clone();
+        let (stop_tx, stop_rx) = channel::<()>();
+        self.stop_tx = Some(stop_tx);
+
+        let slave = self.slave_id;
+        let retries = self.retries;
+        let interval = self.poll_interval.clone();
+
+        self.poll_failures = 0;
+        self.stale_warned = false;
+        self.status = "Auto Poll started...".into();
+        self.logs
+            .push(format!("Auto Poll started ({} ms)", self.poll_interval_ms));
+        self.scroll_to_bottom = true;
+
+        let Some(rt) = &self.rt else {
+            return;
+        };
+
+        rt.spawn(async move {
+            // stop on request, or when the tool dropped its sender
+            while let Err(TryRecvError::Empty) = stop_rx.try_recv() {
+                for &(block, read) in &reads {
+                    let started = Instant::now();
+                    let result = link
+                        .exec_with_retry(
+                            |attempt, delay| Self::send_retry(&msg_tx, attempt, retries, delay),
+                            slave,
+                            read.function,
+                            read.address,
+                            read.quantity,
+                            &[],
+                        )
+                        .await;
+
+                    let message = Self::poll_message(
+                        block,
+                        read.function,
+                        read.address,
+                        read.quantity,
+                        result,
+                        started.elapsed(),
+                    );
+                    if msg_tx.send(message).is_err() {
+                        return;
+                    }
+                }
+
+                let ms = interval.load(Ordering::Relaxed);
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+            }
+        });
+    }
+
+    // A plain value is written straight away. A scaled register takes the
+    // engineering value: it goes through scale / offset in reverse and waits
+    // in pending_write until the user confirmed the raw registers.
+    fn write_cell(&mut self, address: u16, format: DisplayFormat, text: &str) {
+        let Some((scale, offset)) = self.register_map.scaling(address) else {
+            let Some(values) = format.encode(text, self.byte_order) else {
+                self.status = format!("'{}' is not a valid {} value", text.trim(), format.label());
+                return;
+            };
+            self.write_registers(address, values);
+            return;
+        };
+
+        let Ok(entered) = text.trim().parse::<f64>() else {
+            self.status = format!("Invalid value '{}'", text.trim());
+            return;
+        };
+        if scale == 0.0 {
+            self.status = "Scale is 0, there is no raw value to write".into();
+            return;
+        }
+        let exact = (entered - offset) / scale;
+        let raw = match format {
+            DisplayFormat::Float | DisplayFormat::Double => exact,
+            _ => exact.round(),
+        };
+        let Some(values) = format.encode(&raw.to_string(), self.byte_order) else {
+            self.status = format!("Raw value {} does not fit {}", raw, format.label());
+            return;
+        };
+
+        self.pending_write = Some(PendingWrite {
+            address,
+            format,
+            entered,
+            scale,
+            offset,
+            exact,
+            raw,
+            values,
+        });
+    }
+
+    // 06 or 16 depending on how many registers the format takes
+    fn write_registers(&mut self, address: u16, values: Vec<u16>) {
+        let function = if values.len() == 1 {
+            ModbusFunction::WriteSingleRegister
+        } else {
+            ModbusFunction::WriteMultipleRegisters
+        };
+        self.spawn_exec(None, function, address, values.len() as u16, values);
+    }
+
+    fn ui_pending_write(&mut self, ctx: &egui::Context) {
+        let Some(pending) = &self.pending_write else {
+            return;
+        };
+
+        let mut choice = None;
+        egui::Window::new("Confirm write")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                let address = self.address_notation.format(
+                    ModbusFunction::WriteSingleRegister,
+                    pending.address,
+                    self.register_map.display_offset,
+                );
+                let name = self
+                    .register_map
+                    .name(pending.address)
+                    .map(|n| format!(" ({})", n))
+                    .unwrap_or_default();
+                let unit = self
+                    .register_map
+                    .unit(pending.address)
+                    .map(|u| format!(" {}", u))
+                    .unwrap_or_default();
+
+                egui::Grid::new("pending_write")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Register");
+                        ui.label(format!("{}{}, {}", address, name, pending.format.label()));
+                        ui.end_row();
+
+                        ui.label("Entered");
+                        ui.monospace(format!("{}{}", pending.entered, unit));
+                        ui.end_row();
+
+                        ui.label("Raw");
+                        let mut raw = format!(
+                            "({} - {}) / {} = {}",
+                            pending.entered, pending.offset, pending.scale, pending.exact
+                        );
+                        if pending.raw != pending.exact {
+                            raw.push_str(&format!(" → {} (rounded)", pending.raw));
+                        }
+                        ui.monospace(raw);
+                        ui.end_row();
+
+                        ui.label("Registers");
+                        ui.monospace(
+                            pending
+                                .values
+                                .iter()
+                                .map(|v| format!("{:04X} ({})", v, v))
+                                .collect::<Vec<_>>()
+                                .join(" "),
+                        );
+                        ui.end_row();
+
+                        // what the table will show once it's read back
+                        ui.label("Reads back as");
+                        ui.monospace(format!(
+                            "{}{}",
+                            pending.raw * pending.scale + pending.offset,
+                            unit
+                        ));
+                        ui.end_row();
+                    });
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(egui::RichText::new("Write").color(egui::Color32::BLUE))
+                        .clicked()
+                    {
+                        choice = Some(true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        choice = Some(false);
                     }
                 });
+            });
 
-            ui.label("Baud");
-            ui.add(egui::DragValue::new(&mut self.baud_rate));
-        });
+        match choice {
+            Some(true) => {
+                if let Some(pending) = self.pending_write.take() {
+                    self.write_registers(pending.address, pending.values);
+                }
+            }
+            Some(false) => {
+                self.pending_write = None;
+                self.status = "Write cancelled".into();
+            }
+            None => {}
+        }
+    }
 
-        ui.horizontal(|ui| {
-            ui.radio_value(&mut self.data_bits, serialport::DataBits::Eight, "8");
-            ui.radio_value(&mut self.parity, serialport::Parity::None, "N");
-            ui.radio_value(&mut self.parity, serialport::Parity::Even, "E");
-            ui.radio_value(&mut self.parity, serialport::Parity::Odd, "O");
-            ui.radio_value(&mut self.stop_bits, serialport::StopBits::One, "1");
-            ui.radio_value(&mut self.stop_bits, serialport::StopBits::Two, "2");
-        });
+    // 05 from a click on a coil row; the table rereads once it's written
+    fn write_coil(&mut self, address: u16, on: bool) {
+        self.logs.push(format!(
+            "TX toggle coil {} to {}",
+            self.address_notation.format(
+                ModbusFunction::WriteSingleCoil,
+                address,
+                self.register_map.display_offset
+            ),
+            if on { "ON" } else { "OFF" }
+        ));
+        self.scroll_to_bottom = true;
+        self.spawn_exec(
+            None,
+            ModbusFunction::WriteSingleCoil,
+            address,
+            1,
+            vec![on as u16],
+        );
     }
 
-    fn ui_slave(&mut self, ui: &mut egui::Ui) {
-        egui::Frame::group(ui.style()).show(ui, |ui| {
-            ui.set_width(ui.available_width());
-            ui.label(egui::RichText::new("Slave").strong());
+    // a single read of the main range and every block, or the write
+    // described by `write_values`
+    fn exec_once(&mut self) {
+        let values = if self.function.is_write() {
+            match parse_write_values(&self.write_values, self.function) {
+                Ok(values) => values,
+                Err(e) => {
+                    self.status = e;
+                    return;
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        let qty = if self.function.is_write() {
+            values.len() as u16
+        } else {
+            self.quantity
+        };
+        self.spawn_exec(None, self.function, self.address, qty, values);
+
+        if !self.function.is_write() {
+            for (i, block) in self.blocks.clone().into_iter().enumerate() {
+                self.spawn_exec(
+                    self.block_id(Some(i)),
+                    block.function,
+                    block.address,
+                    block.quantity,
+                    Vec::new(),
+                );
+            }
+        }
+    }
 
-            ui.horizontal(|ui| {
-                ui.label("Slave ID");
-                ui.add(egui::DragValue::new(&mut self.slave_id).range(1..=247));
+    // 06/16 (or 05/15) and then a read of the same range, back to back on
+    // the open link so nothing else gets in between
+    fn write_verify(&mut self) {
+        let Some(read_back) = self.function.read_back() else {
+            return;
+        };
+        let values = match parse_write_values(&self.write_values, self.function) {
+            Ok(values) => values,
+            Err(e) => {
+                self.status = e;
+                return;
+            }
+        };
+        let link = match self.link() {
+            Ok(link) => link,
+            Err(e) => {
+                self.status = e;
+                return;
+            }
+        };
+        let Some(rt) = &self.rt else {
+            return;
+        };
 
-                ui.label("Function");
-                egui::ComboBox::from_id_salt("func")
-                    .selected_text(format!("{:?}", self.function))
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(
-                            &mut self.function,
-                            ModbusFunction::ReadCoils,
-                            "01 Read Coils(0x)",
-                        );
-                        ui.selectable_value(
-                            &mut self.function,
-                            ModbusFunction::ReadDiscrete,
-                            "02 Read Discrete Inputs(1x)",
-                        );
-                        ui.selectable_value(
-                            &mut self.function,
-                            ModbusFunction::ReadHolding,
-                            "03 Read Holding Registers(4x)",
-                        );
-                        ui.selectable_value(
-                            &mut self.function,
-                            ModbusFunction::ReadInput,
-                            "04 Read Input Registers(3x)",
-                        );
-                    });
+        self.last_verify = None;
+        let msg_tx = self.tx.clone();
+        let slave = self.slave_id;
+        let function = self.function;
+        let address = self.address;
+        let qty = values.len() as u16;
+        let retries = self.retries;
 
-                ui.label("Address");
-                ui.add(egui::DragValue::new(&mut self.address));
+        rt.spawn(async move {
+            let on_retry = |attempt, delay| Self::send_retry(&msg_tx, attempt, retries, delay);
 
-                ui.label("Quantity");
-                ui.add(egui::DragValue::new(&mut self.quantity).range(1..=125));
-            });
+            let started = Instant::now();
+            let read = match link
+                .exec_pair(on_retry, slave, function, address, &values, read_back)
+                .await
+            {
+                Ok(read) => read,
+                Err(e) => {
+                    let _ = msg_tx.send(Self::poll_message(
+                        None,
+                        function,
+                        address,
+                        qty,
+                        Err(e),
+                        started.elapsed(),
+                    ));
+                    return;
+                }
+            };
+
+            let message = match read {
+                Ok(read) => AppMessage::ModbusVerified {
+                    address,
+                    written: values,
+                    read,
+                    elapsed: started.elapsed(),
+                },
+                Err(e) => {
+                    Self::poll_message(None, read_back, address, qty, Err(e), started.elapsed())
+                }
+            };
+            let _ = msg_tx.send(message);
         });
     }
 
-    fn ui_view(&mut self, ui: &mut egui::Ui) {
-        egui::Frame::group(ui.style()).show(ui, |ui| {
-            ui.set_width(ui.available_width());
-            ui.label(egui::RichText::new("View").strong());
+    fn spawn_exec(
+        &mut self,
+        block: Option<u64>,
+        function: ModbusFunction,
+        addr: u16,
+        qty: u16,
+        values: Vec<u16>,
+    ) {
+        if !self.quantity_ok(&PollBlock {
+            function,
+            address: addr,
+            quantity: qty,
+        }) {
+            return;
+        }
+        let link = match self.link() {
+            Ok(link) => link,
+            Err(e) => {
+                self.status = e;
+                return;
+            }
+        };
+        let Some(rt) = &self.rt else {
+            return;
+        };
 
-            ui.horizontal(|ui| {
-                ui.label(egui::RichText::new("Row: ").strong());
-                ui.radio_value(&mut self.view_rows, 10, "10");
-                ui.radio_value(&mut self.view_rows, 20, "20");
+        let msg_tx = self.tx.clone();
 
-                ui.label(egui::RichText::new("Display: ").strong());
-                egui::ComboBox::from_id_salt("display")
-                    .selected_text(self.display_format.label())
-                    .show_ui(ui, |ui: &mut egui::Ui| {
-                        ui.selectable_value(
-                            &mut self.display_format,
-                            DisplayFormat::Signed,
-                            "Signed",
-                        );
-                        ui.selectable_value(
-                            &mut self.display_format,
-                            DisplayFormat::Unsigned,
-                            "Unsigned",
-                        );
-                        ui.selectable_value(&mut self.display_format, DisplayFormat::Hex, "Hex");
-                        ui.selectable_value(
-                            &mut self.display_format,
-                            DisplayFormat::Binary,
-                            "Binary",
-                        );
-                        ui.selectable_value(&mut self.display_format, DisplayFormat::Long, "Long");
-                        ui.selectable_value(
-                            &mut self.display_format,
-                            DisplayFormat::LongInverse,
-                            "Long Inverse",
-                        );
-                        ui.selectable_value(
-                            &mut self.display_format,
-                            DisplayFormat::Float,
-                            "Float",
-                        );
-                        ui.selectable_value(
-                            &mut self.display_format,
-                            DisplayFormat::FloatInverse,
-                            "Float Inverse",
-                        );
-                        ui.selectable_value(
-                            &mut self.display_format,
-                            DisplayFormat::Double,
-                            "Double",
-                        );
-                        ui.selectable_value(
-                            &mut self.display_format,
-                            DisplayFormat::DoubleInverse,
-                            "Double Inverse",
-                        );
-                    });
-            });
+        let slave = self.slave_id;
+        let retries = self.retries;
+
+        rt.spawn(async move {
+            let started = Instant::now();
+            let result = link
+                .exec_with_retry(
+                    |attempt, delay| Self::send_retry(&msg_tx, attempt, retries, delay),
+                    slave,
+                    function,
+                    addr,
+                    qty,
+                    &values,
+                )
+                .await;
+
+            let _ = msg_tx.send(Self::poll_message(
+                block,
+                function,
+                addr,
+                qty,
+                result,
+                started.elapsed(),
+            ));
         });
+    }
 
-        ui.horizontal(|ui: &mut egui::Ui| {
-            let running = self.stop_tx.is_some();
-
-            if !running {
+    fn ui_scan(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Timeout per id");
+            ui.add(
+                egui::DragValue::new(&mut self.scan_timeout_ms)
+                    .range(20..=5000)
+                    .suffix(" ms"),
+            );
+            if self.scan_stop.is_none() {
                 if ui
-                    .button(egui::RichText::new("▶ Start Auto Poll").color(egui::Color32::BLUE))
+                    .add_enabled(
+                        self.connected,
+                        egui::Button::new(
+                            egui::RichText::new("▶ Scan 1..247").color(egui::Color32::BLUE),
+                        ),
+                    )
+                    .on_hover_text("Read holding register 0 from every slave id")
+                    .on_disabled_hover_text("Connect first")
                     .clicked()
                 {
-                    self.start_auto_poll();
+                    self.start_scan();
                 }
             } else {
                 if ui
-                    .button(egui::RichText::new("⏹ Stop Auto Poll").color(egui::Color32::RED))
+                    .button(egui::RichText::new("⏹ Stop Scan").color(egui::Color32::RED))
                     .clicked()
                 {
-                    self.stop_auto_poll();
+                    self.stop_scan();
+                }
+                ui.add(
+                    egui::ProgressBar::new(self.scan_at as f32 / 247.0)
+                        .desired_width(160.0)
+                        .text(format!("id {}", self.scan_at)),
+                );
+            }
+        });
+
+        let count = |kind: PollFailure| {
+            self.scan_results
+                .iter()
+                .filter(|(_, failure)| matches!(failure, Some((k, _)) if *k == kind))
+                .count()
+        };
+        let (timeouts, errors) = (count(PollFailure::Timeout), count(PollFailure::Transport));
+        let mut picked = None;
+        egui::Grid::new("modbus_scan").striped(true).show(ui, |ui| {
+            for (slave, failure) in &self.scan_results {
+                let text = match failure {
+                    None => "answered".to_string(),
+                    Some((PollFailure::Exception, message)) => format!("exception: {}", message),
+                    Some(_) => continue,
+                };
+                if ui
+                    .button(slave.to_string())
+                    .on_hover_text("Use this slave id")
+                    .clicked()
+                {
+                    picked = Some(*slave);
                 }
+                ui.label(text);
+                ui.end_row();
             }
         });
+        if timeouts + errors > 0 {
+            ui.weak(format!(
+                "{} ids timed out, {} failed with a transport error",
+                timeouts, errors
+            ));
+        }
+        if let Some(slave) = picked {
+            self.slave_id = slave;
+        }
     }
 
-    pub fn ui_table(&mut self, ui: &mut egui::Ui, rows: &mut Vec<ModbusRow>) {
-        egui::ScrollArea::vertical()
-            .auto_shrink([false; 2])
-            .show(ui, |ui| {
-                egui::Grid::new("modbus_table")
-                    .striped(true)
-                    .min_col_width(80.0)
-                    .show(ui, |ui| {
-                        ui.label("Index");
-                        ui.label("Address");
-                        ui.label("Raw");
-                        ui.label("Value");
-                        ui.end_row();
+    // one id after the other on the open link, with a short response timeout
+    // and no retries so silent ids don't hold the scan up; a silent id keeps
+    // the session, connecting still gets the link's own timeout
+    fn start_scan(&mut self) {
+        if self.scan_stop.is_some() {
+            return;
+        }
+        let link = match self.link() {
+            Ok(link) => link
+                .with_request_timeout(Duration::from_millis(self.scan_timeout_ms))
+                .probing(),
+            Err(e) => {
+                self.status = e;
+                return;
+            }
+        };
+        let Some(rt) = &self.rt else {
+            return;
+        };
 
-                        for row in rows.iter_mut() {
-                            ui.label(row.index.to_string());
-                            ui.label(row.address.to_string());
+        let msg_tx = self.tx.clone();
+        let (stop_tx, stop_rx) = channel::<()>();
 
-                            ui.label(
-                                row.raw
-                                    .iter()
-                                    .map(|v| format!("{:04X}", v))
-                                    .collect::<Vec<_>>()
-                                    .join(" "),
-                            );
+        rt.spawn(async move {
+            for slave in 1..=247u8 {
+                if !matches!(stop_rx.try_recv(), Err(TryRecvError::Empty)) {
+                    return;
+                }
+                let started = Instant::now();
+                let result = link
+                    .exec(slave, ModbusFunction::ReadHolding, 0, 1, &[])
+                    .await;
+                let failure = result.err().map(|e| {
+                    let message = match e.downcast_ref::<ExceptionCode>() {
+                        Some(code) => exception_text(code),
+                        None => e.to_string(),
+                    };
+                    (PollFailure::classify(&e), message)
+                });
+                let message = AppMessage::ModbusScanned {
+                    slave,
+                    failure,
+                    elapsed: started.elapsed(),
+                };
+                if msg_tx.send(message).is_err() {
+                    return;
+                }
+            }
+            let _ = msg_tx.send(AppMessage::ModbusScanDone);
+        });
 
-                            ui.label(row.format.format(&row.raw));
-                            ui.end_row();
-                        }
-                    });
-            });
+        self.scan_stop = Some(stop_tx);
+        self.scan_at = 0;
+        self.scan_results.clear();
+        self.status = "Scanning slave ids...".into();
+        self.logs
+            .push(format!("SCAN started, {} ms per id", self.scan_timeout_ms));
+        self.scroll_to_bottom = true;
     }
 
-    fn ui_logs(&mut self, ui: &mut egui::Ui) {
-        egui::Frame::group(ui.style()).show(ui, |ui| {
-            egui::ScrollArea::vertical()
-                .stick_to_bottom(self.scroll_to_bottom)
-                .show(ui, |ui| {
-                    for log in &self.logs {
-                        ui.label(log);
+    // the task ends once the id in flight has been answered or timed out
+    fn stop_scan(&mut self) {
+        if let Some(stop) = self.scan_stop.take() {
+            let _ = stop.send(());
+            self.status = format!(
+                "Scan stopped at id {}, {}",
+                self.scan_at,
+                self.scan_summary()
+            );
+            self.logs.push(format!("SCAN {}", self.status));
+            self.scroll_to_bottom = true;
+        }
+    }
+
+    fn scan_summary(&self) -> String {
+        let answered = self
+            .scan_results
+            .iter()
+            .filter(|(_, failure)| scan_answered(failure))
+            .count();
+        format!("{} of {} ids answered", answered, self.scan_results.len())
+    }
+
+    fn ui_sweep(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("sweep_function")
+                .selected_text(self.sweep_function.label())
+                .show_ui(ui, |ui| {
+                    for function in ModbusFunction::ALL {
+                        if !function.is_write() {
+                            ui.selectable_value(
+                                &mut self.sweep_function,
+                                function,
+                                function.label(),
+                            );
+                        }
                     }
                 });
+            ui.label("From");
+            ui.add(egui::DragValue::new(&mut self.sweep_from));
+            ui.label("To");
+            ui.add(egui::DragValue::new(&mut self.sweep_to).range(self.sweep_from..=u16::MAX));
 
-            self.scroll_to_bottom = false;
+            if self.sweep_stop.is_none() {
+                if ui
+                    .add_enabled(
+                        self.connected,
+                        egui::Button::new(
+                            egui::RichText::new("▶ Sweep").color(egui::Color32::BLUE),
+                        ),
+                    )
+                    .on_hover_text(format!(
+                        "Read the range {} at a time and list what isn't zero",
+                        self.sweep_function.max_quantity()
+                    ))
+                    .on_disabled_hover_text("Connect first")
+                    .clicked()
+                {
+                    self.start_sweep();
+                }
+            } else {
+                if ui
+                    .button(egui::RichText::new("⏹ Stop Sweep").color(egui::Color32::RED))
+                    .clicked()
+                {
+                    self.stop_sweep();
+                }
+                ui.add(
+                    egui::ProgressBar::new(self.sweep_done as f32 / self.sweep_total as f32)
+                        .desired_width(160.0)
+                        .show_percentage(),
+                );
+            }
         });
-    }
 
-    fn start_auto_poll(&mut self) {
-        if self.stop_tx.is_some() {
+        if self.sweep_done == 0 {
             return;
         }
+        ui.label(self.sweep_summary());
+        if !self.sweep_errors.is_empty() {
+            let listed: Vec<String> = self
+                .sweep_errors
+                .iter()
+                .take(10)
+                .map(|(address, quantity, e)| format!("{} x{}: {}", address, quantity, e))
+                .collect();
+            ui.weak(format!("{} ranges unreadable", self.sweep_errors.len()))
+                .on_hover_text(listed.join("\n"));
+        }
 
-        let (data_tx, data_rx) = channel::<Vec<u16>>();
-        let (stop_tx, stop_rx) = channel::<()>();
-        let (status_tx, status_rx) = channel::<String>();
-
-        self.rx = Some(data_rx);
-        self.stop_tx = Some(stop_tx);
-        self.status_rx = Some(status_rx);
-
-        let ip = self.tcp_ip.clone();
-        let port = self.tcp_port;
-        let slave = self.slave_id;
-        let addr = self.address;
-        let qty = self.quantity;
-        let function = self.function;
+        let mut load = None;
+        egui::Grid::new("modbus_sweep")
+            .striped(true)
+            .show(ui, |ui| {
+                for (first, count) in populated_blocks(self.sweep_values.keys().copied()) {
+                    ui.label(format!(
+                        "{} .. {}",
+                        self.address_notation.format(
+                            self.sweep_function,
+                            first,
+                            self.register_map.display_offset
+                        ),
+                        self.address_notation.format(
+                            self.sweep_function,
+                            first.wrapping_add(count - 1),
+                            self.register_map.display_offset
+                        )
+                    ));
+                    ui.label(format!("{} populated", count));
+                    let preview: Vec<String> = self
+                        .sweep_values
+                        .range(first..)
+                        .take(count.min(4) as usize)
+                        .map(|(_, v)| format!("{:04X}", v))
+                        .collect();
+                    ui.monospace(if count > 4 {
+                        format!("{} ...", preview.join(" "))
+                    } else {
+                        preview.join(" ")
+                    });
+                    if ui
+                        .button("Load")
+                        .on_hover_text("Show this block in the table")
+                        .clicked()
+                    {
+                        load = Some((first, count));
+                    }
+                    ui.end_row();
+                }
+            });
 
-        self.status = "Auto Poll started...".into();
-        self.logs.push("Auto Poll started (1s)".into());
-        self.scroll_to_bottom = true;
+        if let Some((first, count)) = load {
+            self.function = self.sweep_function;
+            self.address = first;
+            self.quantity = count.min(self.sweep_function.max_quantity());
+            self.trend.clear();
+            self.auto_read_due = None;
+            self.read_now();
+        }
+    }
 
-        self.rt.spawn(async move {
-            if stop_rx.try_recv().is_ok() {
+    // chunks of max_quantity; one the device rejects is halved until the
+    // rejected addresses are found, so a single gap doesn't hide its neighbours.
+    // Illegal Function is about the function, not the range, so it isn't split.
+    fn start_sweep(&mut self) {
+        if self.sweep_stop.is_some() || self.sweep_function.is_write() {
+            return;
+        }
+        if self.sweep_to < self.sweep_from {
+            self.status = "Sweep range is empty, To is below From".into();
+            return;
+        }
+        let link = match self.link() {
+            Ok(link) => link,
+            Err(e) => {
+                self.status = e;
                 return;
             }
+        };
+        let Some(rt) = &self.rt else {
+            return;
+        };
 
-            match Self::modbus_read_by_function(ip.clone(), port, slave, function, addr, qty).await
-            {
-                Ok(data) => {
-                    let _ = data_tx.send(data);
+        let msg_tx = self.tx.clone();
+        let (stop_tx, stop_rx) = channel::<()>();
+        let slave = self.slave_id;
+        let function = self.sweep_function;
+        let mut pending = sweep::chunks(self.sweep_from, self.sweep_to, function.max_quantity());
+        pending.reverse();
+
+        rt.spawn(async move {
+            while let Some((address, quantity)) = pending.pop() {
+                if !matches!(stop_rx.try_recv(), Err(TryRecvError::Empty)) {
+                    return;
                 }
-                Err(e) => {
-                    let _ = status_tx.send(format!("Read error: {}", e));
+                let result = link.exec(slave, function, address, quantity, &[]).await;
+
+                let result = match result {
+                    Ok(data) => Ok(data),
+                    Err(e)
+                        if quantity > 1
+                            && matches!(
+                                e.downcast_ref::<ExceptionCode>(),
+                                Some(code) if *code != ExceptionCode::IllegalFunction
+                            ) =>
+                    {
+                        let half = quantity / 2;
+                        pending.push((address + half, quantity - half));
+                        pending.push((address, half));
+                        continue;
+                    }
+                    Err(e) => Err(match e.downcast_ref::<ExceptionCode>() {
+                        Some(code) => exception_text(code),
+                        None => e.to_string(),
+                    }),
+                };
+                let message = AppMessage::ModbusSwept {
+                    address,
+                    quantity,
+                    result,
+                };
+                if msg_tx.send(message).is_err() {
+                    return;
                 }
             }
-
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            let _ = msg_tx.send(AppMessage::ModbusSweepDone);
         });
+
+        self.sweep_stop = Some(stop_tx);
+        self.sweep_done = 0;
+        self.sweep_total = self.sweep_to as u32 - self.sweep_from as u32 + 1;
+        self.sweep_values.clear();
+        self.sweep_zeros = 0;
+        self.sweep_errors.clear();
+        self.status = "Sweeping registers...".into();
+        self.logs.push(format!(
+            "SWEEP started, {} {}..={}",
+            function.label(),
+            self.sweep_from,
+            self.sweep_to
+        ));
+        self.scroll_to_bottom = true;
+    }
+
+    fn stop_sweep(&mut self) {
+        if let Some(stop) = self.sweep_stop.take() {
+            let _ = stop.send(());
+            self.status = format!("Sweep stopped, {}", self.sweep_summary());
+            self.logs.push(format!("SWEEP {}", self.status));
+            self.scroll_to_bottom = true;
+        }
+    }
+
+    fn sweep_summary(&self) -> String {
+        format!(
+            "{} populated, {} zero, {} unreadable of {} addresses",
+            self.sweep_values.len(),
+            self.sweep_zeros,
+            self.sweep_errors
+                .iter()
+                .map(|(_, quantity, _)| *quantity as u32)
+                .sum::<u32>(),
+            self.sweep_done
+        )
     }
 
     pub fn stop_auto_poll(&mut self) {
+        // keep the runtime error visible
+        if self.rt.is_none() {
+            return;
+        }
+
         if let Some(stop_tx) = self.stop_tx.take() {
             let _ = stop_tx.send(());
         }
+        self.trend.clear();
+        self.poll_failures = 0;
+        self.stale_warned = false;
 
-        self.rx = None;
         self.status = "Auto Poll stopped".into();
         self.scroll_to_bottom = true;
     }
 
-    async fn modbus_read_by_function(
-        ip: String,
-        port: u16,
+    fn send_retry(tx: &Sender<AppMessage>, attempt: u32, retries: u32, delay: Duration) {
+        let _ = tx.send(AppMessage::ModbusRetry {
+            attempt,
+            retries,
+            delay,
+        });
+    }
+
+    fn poll_message(
+        block: Option<u64>,
+        function: ModbusFunction,
+        address: u16,
+        quantity: u16,
+        result: Result<Vec<u16>, Error>,
+        elapsed: Duration,
+    ) -> AppMessage {
+        match result {
+            Ok(values) if function.is_write() => AppMessage::ModbusWritten {
+                address,
+                values,
+                elapsed,
+            },
+            Ok(data) => AppMessage::ModbusData {
+                block,
+                data,
+                elapsed,
+            },
+            Err(e) => AppMessage::ModbusError {
+                function,
+                address,
+                quantity,
+                kind: PollFailure::classify(&e),
+                message: match e.downcast_ref::<ExceptionCode>() {
+                    Some(code) => exception_text(code),
+                    None => e.to_string(),
+                },
+                elapsed,
+            },
+        }
+    }
+
+    // reads return the data, writes echo the values that were written
+    async fn modbus_exec(
+        ctx: &mut client::Context,
         slave_id: u8,
         function: ModbusFunction,
         address: u16,
         quantity: u16,
+        values: &[u16],
     ) -> Result<Vec<u16>, Error> {
-        let socket_addr: SocketAddr = format!("{}:{}", ip, port).parse()?;
-
-        let mut ctx = tcp::connect(socket_addr).await?;
         ctx.set_slave(Slave(slave_id));
 
         let data: Vec<u16> = match function {
@@ -470,33 +3365,199 @@ impl ModbusTool {
                 let response = ctx.read_input_registers(address, quantity).await??;
                 response.into_iter().map(|r| r as u16).collect()
             }
+
+            ModbusFunction::WriteSingleCoil => {
+                ctx.write_single_coil(address, values[0] != 0).await??;
+                values.to_vec()
+            }
+
+            ModbusFunction::WriteSingleRegister => {
+                ctx.write_single_register(address, values[0]).await??;
+                values.to_vec()
+            }
+
+            ModbusFunction::WriteMultipleCoils => {
+                let coils: Vec<bool> = values.iter().map(|v| *v != 0).collect();
+                ctx.write_multiple_coils(address, &coils).await??;
+                values.to_vec()
+            }
+
+            ModbusFunction::WriteMultipleRegisters => {
+                ctx.write_multiple_registers(address, values).await??;
+                values.to_vec()
+            }
         };
 
         Ok(data)
     }
 
-    fn build_rows(
-        start_addr: u16,
-        regs: &[u16],
-        rows: usize,
-        format: DisplayFormat,
-    ) -> Vec<ModbusRow> {
-        let reg_per_row = format.register_count();
+    // devices answer an oversized request with an exception at best, don't send it
+    fn quantity_ok(&mut self, read: &PollBlock) -> bool {
+        let max = read.function.max_quantity();
+        if (1..=max).contains(&read.quantity) {
+            return true;
+        }
+        self.status = format!(
+            "{} takes a quantity of 1..={}, not {}",
+            read.function.label(),
+            max,
+            read.quantity
+        );
+        self.logs.push(format!(
+            "ERR {}: quantity out of range 1..={}",
+            self.block_title(read),
+            max
+        ));
+        self.scroll_to_bottom = true;
+        false
+    }
+
+    fn main_block(&self) -> PollBlock {
+        PollBlock {
+            function: self.function,
+            address: self.address,
+            quantity: self.quantity,
+        }
+    }
+
+    // the range behind a table row and what was last read from it
+    fn read_of(&self, block: Option<usize>) -> (PollBlock, &[u16]) {
+        match block {
+            Some(i) => (self.blocks[i], &self.block_data[i].data),
+            None => (self.main_block(), &self.data),
+        }
+    }
+
+    // one titled section per read; the main one shows view_rows rows,
+    // a block exactly the registers it covers
+    fn build_sections(&self) -> Vec<(String, Vec<ModbusRow>)> {
+        let mut sections = vec![(String::new(), self.build_rows(None, self.view_rows))];
+
+        for (i, block) in self.blocks.iter().enumerate() {
+            let mut rows = self.build_rows(Some(i), block.quantity as usize);
+            rows.retain(|row| row.address.wrapping_sub(block.address) < block.quantity);
+            sections.push((self.block_title(block), rows));
+        }
+        if sections.len() > 1 {
+            sections[0].0 = self.block_title(&self.main_block());
+        }
+        sections
+    }
+
+    // "ReadHolding @40101 x6", in the notation the table uses
+    fn block_title(&self, block: &PollBlock) -> String {
+        format!(
+            "{:?} @{} x{}",
+            block.function,
+            self.address_notation.format(
+                block.function,
+                block.address,
+                self.register_map.display_offset
+            ),
+            block.quantity
+        )
+    }
+
+    // rows take the table format unless one was picked for their address,
+    // each row then spans as many registers as its own format needs
+    fn build_rows(&self, block: Option<usize>, rows: usize) -> Vec<ModbusRow> {
+        let order = self.byte_order;
+        let (read, data) = self.read_of(block);
+        let mut start = 0;
 
         (0..rows)
             .map(|i| {
-                let addr = start_addr + (i * reg_per_row) as u16;
-                let start = i * reg_per_row;
-                let raw = regs.get(start..start + reg_per_row).unwrap_or(&[]).to_vec();
+                if read.function.reads_bits() {
+                    return self.bit_row(block, i, read.address, data);
+                }
+                let addr = read.address.wrapping_add(start as u16);
+                let format = self
+                    .row_formats
+                    .get(&addr)
+                    .copied()
+                    .unwrap_or(self.display_format);
+                let reg_per_row = format.row_span(data.len().saturating_sub(start));
+                let raw = data.get(start..start + reg_per_row).unwrap_or(&[]).to_vec();
+                start += reg_per_row;
+
+                let mut value = format.format(&raw, order, self.decimals);
+                let (scale, offset) = self.register_map.scaling(addr).unwrap_or((1.0, 0.0));
+                let mut unscaled = None;
+                if raw.len() == reg_per_row && (scale, offset) != (1.0, 0.0) {
+                    if let Some(v) = format.value(&raw, order) {
+                        unscaled = Some(std::mem::replace(
+                            &mut value,
+                            format_number(v * scale + offset),
+                        ));
+                    }
+                }
+                let plain = value.clone();
+                if format.is_binary() && self.binary_grouped && raw.len() == reg_per_row {
+                    value = group_bits(&value);
+                }
+                if let Some(unit) = self.register_map.unit(addr) {
+                    if !format.is_binary() && raw.len() == reg_per_row {
+                        value = format!("{} {}", value, unit);
+                    }
+                }
 
                 ModbusRow {
+                    block,
                     index: i,
                     address: addr,
+                    name: self.register_map.name(addr).unwrap_or_default().to_string(),
                     raw: raw.clone(),
                     format,
-                    value: format.format(&raw),
+                    value,
+                    plain,
+                    unscaled,
+                    scale,
+                    offset,
+                    valid: format.is_valid(&raw, order),
+                    lookup: match raw.as_slice() {
+                        [v] => self.register_map.lookup(addr, *v).map(|s| s.to_string()),
+                        _ => None,
+                    },
                 }
             })
             .collect()
     }
+
+    // one coil / discrete input per row whatever the table format, as ON / OFF
+    fn bit_row(&self, block: Option<usize>, i: usize, start: u16, data: &[u16]) -> ModbusRow {
+        let addr = start.wrapping_add(i as u16);
+        let raw: Vec<u16> = data.get(i).copied().into_iter().collect();
+        let value = match raw.first() {
+            Some(0) => "OFF",
+            Some(_) => "ON",
+            None => "-",
+        };
+        ModbusRow {
+            block,
+            index: i,
+            address: addr,
+            name: self.register_map.name(addr).unwrap_or_default().to_string(),
+            raw,
+            format: self
+                .row_formats
+                .get(&addr)
+                .copied()
+                .unwrap_or(self.display_format),
+            value: value.to_string(),
+            plain: value.to_string(),
+            unscaled: None,
+            scale: 1.0,
+            offset: 0.0,
+            valid: true,
+            lookup: None,
+        }
+    }
+}
+
+// an exception still means a device is listening at that id
+fn scan_answered(failure: &Option<(PollFailure, String)>) -> bool {
+    !matches!(failure, Some((kind, _)) if *kind != PollFailure::Exception)
 }
+
+#[cfg(test)]
+mod tests;
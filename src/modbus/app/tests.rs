@@ -0,0 +1,278 @@
+// Read path against an in-process Modbus/TCP server with known contents.
+use super::*;
+use std::future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::net::TcpListener;
+use tokio_modbus::server::tcp::{accept_tcp_connection, Server};
+use tokio_modbus::server::Service;
+use tokio_modbus::{ExceptionCode, Request, Response};
+
+// addresses 0..16 exist: registers hold 100 + address, bits are set on even addresses
+const SIZE: u16 = 16;
+
+fn registers(address: u16, quantity: u16) -> Result<Vec<u16>, ExceptionCode> {
+    if address as u32 + quantity as u32 > SIZE as u32 {
+        return Err(ExceptionCode::IllegalDataAddress);
+    }
+    Ok((address..address + quantity).map(|a| 100 + a).collect())
+}
+
+fn bits(address: u16, quantity: u16) -> Result<Vec<bool>, ExceptionCode> {
+    if address as u32 + quantity as u32 > SIZE as u32 {
+        return Err(ExceptionCode::IllegalDataAddress);
+    }
+    Ok((address..address + quantity).map(|a| a % 2 == 0).collect())
+}
+
+struct MockDevice;
+
+impl Service for MockDevice {
+    type Request = Request<'static>;
+    type Response = Response;
+    type Exception = ExceptionCode;
+    type Future = future::Ready<Result<Response, ExceptionCode>>;
+
+    fn call(&self, req: Self::Request) -> Self::Future {
+        let res = match req {
+            Request::ReadCoils(a, q) => bits(a, q).map(Response::ReadCoils),
+            Request::ReadDiscreteInputs(a, q) => bits(a, q).map(Response::ReadDiscreteInputs),
+            Request::ReadHoldingRegisters(a, q) => {
+                registers(a, q).map(Response::ReadHoldingRegisters)
+            }
+            Request::ReadInputRegisters(a, q) => registers(a, q).map(Response::ReadInputRegisters),
+            _ => Err(ExceptionCode::IllegalFunction),
+        };
+        future::ready(res)
+    }
+}
+
+async fn start_mock() -> Endpoint {
+    start_counting_mock().await.0
+}
+
+// also reports how many TCP sessions the server has accepted
+async fn start_counting_mock() -> (Endpoint, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let sessions = Arc::new(AtomicUsize::new(0));
+    let counter = sessions.clone();
+
+    tokio::spawn(async move {
+        let server = Server::new(listener);
+        let new_service = |_addr| Ok(Some(MockDevice));
+        let on_connected = move |stream, addr| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move { accept_tcp_connection(stream, addr, new_service) }
+        };
+        let _ = server.serve(&on_connected, |_err| {}).await;
+    });
+
+    let endpoint = Endpoint::Tcp {
+        ip: "127.0.0.1".into(),
+        port,
+    };
+    (endpoint, sessions)
+}
+
+async fn read(function: ModbusFunction, address: u16, quantity: u16) -> Result<Vec<u16>, Error> {
+    let endpoint = start_mock().await;
+    Link::new(endpoint)
+        .exec(1, function, address, quantity, &[])
+        .await
+}
+
+#[tokio::test]
+async fn reads_share_one_session() {
+    let (endpoint, sessions) = start_counting_mock().await;
+    let link = Link::new(endpoint);
+
+    for address in 0..3 {
+        let data = link
+            .exec(1, ModbusFunction::ReadHolding, address, 1, &[])
+            .await
+            .unwrap();
+        assert_eq!(data, vec![100 + address]);
+    }
+    assert_eq!(sessions.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn reads_holding_registers() {
+    let data = read(ModbusFunction::ReadHolding, 2, 3).await.unwrap();
+    assert_eq!(data, vec![102, 103, 104]);
+}
+
+#[tokio::test]
+async fn reads_input_registers() {
+    let data = read(ModbusFunction::ReadInput, 0, 4).await.unwrap();
+    assert_eq!(data, vec![100, 101, 102, 103]);
+}
+
+#[tokio::test]
+async fn reads_coils() {
+    let data = read(ModbusFunction::ReadCoils, 0, 5).await.unwrap();
+    assert_eq!(data, vec![1, 0, 1, 0, 1]);
+}
+
+#[tokio::test]
+async fn reads_discrete_inputs() {
+    let data = read(ModbusFunction::ReadDiscrete, 3, 2).await.unwrap();
+    assert_eq!(data, vec![0, 1]);
+}
+
+#[tokio::test]
+async fn exception_response_is_classified() {
+    let err = read(ModbusFunction::ReadHolding, 10, 10).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ExceptionCode>(),
+        Some(&ExceptionCode::IllegalDataAddress)
+    );
+    assert_eq!(
+        exception_text(&ExceptionCode::IllegalDataAddress),
+        "02 Illegal Data Address"
+    );
+    assert_eq!(PollFailure::classify(&err), PollFailure::Exception);
+}
+
+// accepts the connection but never answers
+async fn silent_endpoint() -> Endpoint {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (_stream, _) = listener.accept().await.unwrap();
+        future::pending::<()>().await;
+    });
+
+    Endpoint::Tcp {
+        ip: "127.0.0.1".into(),
+        port,
+    }
+}
+
+#[tokio::test]
+async fn silent_device_times_out() {
+    let link = Link::new(silent_endpoint().await);
+    let read = link.exec(1, ModbusFunction::ReadHolding, 0, 1, &[]);
+    let err: Error = tokio::time::timeout(Duration::from_millis(200), read)
+        .await
+        .unwrap_err()
+        .into();
+    assert_eq!(PollFailure::classify(&err), PollFailure::Timeout);
+}
+
+#[tokio::test]
+async fn request_timeout_is_enforced_by_the_link() {
+    let link = Link::new(silent_endpoint().await).with_timeout(Duration::from_millis(100));
+    let err = link
+        .exec(1, ModbusFunction::ReadHolding, 0, 1, &[])
+        .await
+        .unwrap_err();
+    assert_eq!(PollFailure::classify(&err), PollFailure::Timeout);
+    assert_eq!(err.to_string(), "request timeout");
+}
+
+// a free port with nothing listening on it
+async fn refusing_endpoint() -> Endpoint {
+    let port = {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port()
+    };
+    Endpoint::Tcp {
+        ip: "127.0.0.1".into(),
+        port,
+    }
+}
+
+#[tokio::test]
+async fn refused_connection_is_transport_error() {
+    let endpoint = refusing_endpoint().await;
+    let err = Link::new(endpoint)
+        .exec(1, ModbusFunction::ReadHolding, 0, 1, &[])
+        .await
+        .unwrap_err();
+    assert_eq!(PollFailure::classify(&err), PollFailure::Transport);
+}
+
+#[tokio::test]
+async fn transport_errors_are_retried_with_backoff() {
+    let link = Link::new(refusing_endpoint().await).with_retry(RetryPolicy {
+        retries: 2,
+        base_delay: Duration::from_millis(1),
+    });
+
+    let retries = std::sync::Mutex::new(Vec::new());
+    let err = link
+        .exec_with_retry(
+            |attempt, delay| retries.lock().unwrap().push((attempt, delay)),
+            1,
+            ModbusFunction::ReadHolding,
+            0,
+            1,
+            &[],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(PollFailure::classify(&err), PollFailure::Transport);
+    assert_eq!(
+        *retries.lock().unwrap(),
+        vec![(1, Duration::from_millis(1)), (2, Duration::from_millis(2))]
+    );
+}
+
+#[test]
+fn block_results_follow_the_block_not_its_index() {
+    let mut tool = ModbusTool::new();
+    for address in [0, 100] {
+        tool.blocks.push(PollBlock {
+            function: ModbusFunction::ReadHolding,
+            address,
+            quantity: 1,
+        });
+        let data = tool.new_block_data();
+        tool.block_data.push(data);
+    }
+    let (first, second) = (tool.block_data[0].id, tool.block_data[1].id);
+
+    // the first block goes while its read is still in flight
+    tool.blocks.remove(0);
+    tool.block_data.remove(0);
+    for (block, value) in [(first, 1), (second, 2)] {
+        tool.handle_message(AppMessage::ModbusData {
+            block: Some(block),
+            data: vec![value],
+            elapsed: Duration::ZERO,
+        });
+    }
+
+    assert_eq!(tool.block_data.len(), 1);
+    assert_eq!(tool.block_data[0].data, [2]);
+}
+
+#[test]
+fn stale_warning_fires_once_when_the_threshold_is_lowered() {
+    let mut tool = ModbusTool::new();
+    let (stop_tx, _stop_rx) = channel::<()>();
+    tool.stop_tx = Some(stop_tx);
+    tool.stale_after = 5;
+    let timeout = || AppMessage::ModbusError {
+        function: ModbusFunction::ReadHolding,
+        address: 0,
+        quantity: 1,
+        kind: PollFailure::Timeout,
+        message: "timed out".into(),
+        elapsed: Duration::ZERO,
+    };
+    let warnings = |tool: &ModbusTool| tool.logs.iter().filter(|l| l.starts_with("WARN")).count();
+
+    for _ in 0..3 {
+        tool.handle_message(timeout());
+    }
+    assert_eq!(warnings(&tool), 0);
+
+    // already past the new threshold, the next failure warns
+    tool.stale_after = 2;
+    tool.handle_message(timeout());
+    tool.handle_message(timeout());
+    assert_eq!(warnings(&tool), 1);
+}
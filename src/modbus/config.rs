@@ -0,0 +1,164 @@
+use super::app::{ModbusFunction, ModbusMode, PollBlock};
+use super::display::MAX_DECIMALS;
+use super::display::{ByteOrder, DisplayFormat, StoredFormat};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serialport::{DataBits, FlowControl, Parity, StopBits};
+use std::fs;
+
+pub const CONFIG_FILE: &str = "modbus_config.json";
+
+// The settings worth keeping between launches. Missing fields take their
+// default, so files written by older versions still load.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModbusConfig {
+    pub mode: ModbusMode,
+    pub tcp_ip: String,
+    pub tcp_port: u16,
+    pub tls_port: u16,
+    pub rtu_port: Option<String>,
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+    pub retries: u32,
+    pub retry_delay_ms: u64,
+    pub timeout_ms: u64,
+
+    pub slave_id: u8,
+    pub function: ModbusFunction,
+    pub address: u16,
+    pub quantity: u16,
+    pub blocks: Vec<PollBlock>,
+
+    pub view_rows: usize,
+    // an old *Inverse format is folded into byte_order on load
+    pub display_format: StoredFormat,
+    pub byte_order: ByteOrder,
+    pub decimals: usize,
+    pub poll_interval_ms: u64,
+    pub stale_after: u32,
+}
+
+impl Default for ModbusConfig {
+    fn default() -> Self {
+        ModbusConfig {
+            mode: ModbusMode::Tcp,
+            tcp_ip: "127.0.0.1".into(),
+            tcp_port: 502,
+            tls_port: 802,
+            rtu_port: None,
+            baud_rate: 9600,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+            retries: 2,
+            retry_delay_ms: 100,
+            timeout_ms: 3000,
+
+            slave_id: 1,
+            function: ModbusFunction::ReadHolding,
+            address: 0,
+            quantity: 10,
+            blocks: Vec::new(),
+
+            view_rows: 10,
+            display_format: DisplayFormat::Signed.into(),
+            byte_order: ByteOrder::Abcd,
+            decimals: 3,
+            poll_interval_ms: 1000,
+            stale_after: 5,
+        }
+    }
+}
+
+impl ModbusConfig {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let config: ModbusConfig = serde_json::from_str(&text)?;
+        Ok(config.validated())
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // hand-edited files can hold anything, pull values back into what the UI allows
+    fn validated(mut self) -> Self {
+        if self.display_format.inverse {
+            self.display_format.inverse = false;
+            self.byte_order = self.byte_order.reversed_words();
+        }
+        self.slave_id = self.slave_id.clamp(1, 247);
+        // writes take their quantity from the values, keep the read one
+        if !self.function.is_write() {
+            self.quantity = self.quantity.clamp(1, self.function.max_quantity());
+        }
+        self.blocks.retain(|b| !b.function.is_write());
+        for block in &mut self.blocks {
+            block.quantity = block.quantity.clamp(1, block.function.max_quantity());
+        }
+        self.retries = self.retries.min(10);
+        self.retry_delay_ms = self.retry_delay_ms.clamp(10, 10000);
+        self.timeout_ms = self.timeout_ms.clamp(100, 60000);
+        self.poll_interval_ms = self.poll_interval_ms.clamp(50, 60000);
+        self.stale_after = self.stale_after.clamp(1, 100);
+        self.decimals = self.decimals.min(MAX_DECIMALS);
+        if self.view_rows != 20 {
+            self.view_rows = 10;
+        }
+        if self.baud_rate == 0 {
+            self.baud_rate = 9600;
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_inverse_format_loads_as_cdab() {
+        let text = r#"{ "display_format": "FloatInverse" }"#;
+        let config = serde_json::from_str::<ModbusConfig>(text)
+            .unwrap()
+            .validated();
+
+        assert_eq!(config.display_format.format, DisplayFormat::Float);
+        assert_eq!(config.byte_order, ByteOrder::Cdab);
+        assert_eq!(
+            config
+                .display_format
+                .format
+                .format(&[0xE979, 0xC2F6], config.byte_order, 3),
+            "-123.456"
+        );
+    }
+
+    #[test]
+    fn rtu_line_settings_round_trip() {
+        let config = ModbusConfig {
+            baud_rate: 19200,
+            data_bits: DataBits::Seven,
+            parity: Parity::Even,
+            stop_bits: StopBits::Two,
+            flow_control: FlowControl::Hardware,
+            ..ModbusConfig::default()
+        };
+        let text = serde_json::to_string(&config).unwrap();
+        let loaded = serde_json::from_str::<ModbusConfig>(&text)
+            .unwrap()
+            .validated();
+
+        assert_eq!(loaded.baud_rate, 19200);
+        assert_eq!(loaded.data_bits, DataBits::Seven);
+        assert_eq!(loaded.parity, Parity::Even);
+        assert_eq!(loaded.stop_bits, StopBits::Two);
+        assert_eq!(loaded.flow_control, FlowControl::Hardware);
+    }
+}
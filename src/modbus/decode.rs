@@ -0,0 +1,343 @@
+use super::display::ByteOrder;
+use crate::serial::utils::bytes_to_ascii_dots;
+
+// A way of turning a run of registers into display text.
+// New formats are added as implementors and mapped from `DisplayFormat::decoder`.
+pub trait Decoder: Sync {
+    // `decimals` is for the float formats, the others ignore it
+    fn decode(&self, regs: &[u16], order: ByteOrder, decimals: usize) -> String;
+
+    // 0 = takes every register that is left in the read
+    fn register_count(&self) -> usize;
+
+    // the decoded number, for formats that have one (used by the trend plot)
+    fn value(&self, _regs: &[u16], _order: ByteOrder) -> Option<f64> {
+        None
+    }
+
+    // the registers that decode back to `text`, for formats that can be written
+    fn encode(&self, _text: &str, _order: ByteOrder) -> Option<Vec<u16>> {
+        None
+    }
+
+    // false when the registers don't decode to a usable number (NaN / Inf floats)
+    fn is_valid(&self, _regs: &[u16], _order: ByteOrder) -> bool {
+        true
+    }
+}
+
+// the first `count` registers, reordered to big-endian words
+fn words(regs: &[u16], count: usize, order: ByteOrder) -> Option<Vec<u16>> {
+    regs.get(..count).map(|regs| order.apply(regs))
+}
+
+fn to_u32(words: &[u16]) -> u32 {
+    ((words[0] as u32) << 16) | words[1] as u32
+}
+
+// spelled out, "inf" next to real readings looks like a bug in the tool
+fn float_text(v: f64, decimals: usize) -> String {
+    if v.is_nan() {
+        "NaN".into()
+    } else if v.is_infinite() {
+        if v > 0.0 { "+Inf" } else { "-Inf" }.into()
+    } else {
+        format!("{:.*}", decimals, v)
+    }
+}
+
+fn to_u64(words: &[u16]) -> u64 {
+    ((words[0] as u64) << 48)
+        | ((words[1] as u64) << 32)
+        | ((words[2] as u64) << 16)
+        | (words[3] as u64)
+}
+
+pub struct Signed;
+
+impl Decoder for Signed {
+    fn decode(&self, regs: &[u16], _order: ByteOrder, _decimals: usize) -> String {
+        regs.first()
+            .map(|v| (*v as i16).to_string())
+            .unwrap_or("-".into())
+    }
+
+    fn value(&self, regs: &[u16], _order: ByteOrder) -> Option<f64> {
+        regs.first().map(|v| *v as i16 as f64)
+    }
+
+    fn encode(&self, text: &str, _order: ByteOrder) -> Option<Vec<u16>> {
+        text.parse::<i16>().ok().map(|v| vec![v as u16])
+    }
+
+    fn register_count(&self) -> usize {
+        1
+    }
+}
+
+pub struct Unsigned;
+
+impl Decoder for Unsigned {
+    fn decode(&self, regs: &[u16], _order: ByteOrder, _decimals: usize) -> String {
+        regs.first().map(|v| v.to_string()).unwrap_or("-".into())
+    }
+
+    fn value(&self, regs: &[u16], _order: ByteOrder) -> Option<f64> {
+        regs.first().map(|v| *v as f64)
+    }
+
+    fn encode(&self, text: &str, _order: ByteOrder) -> Option<Vec<u16>> {
+        text.parse::<u16>().ok().map(|v| vec![v])
+    }
+
+    fn register_count(&self) -> usize {
+        1
+    }
+}
+
+pub struct Hex;
+
+impl Decoder for Hex {
+    fn decode(&self, regs: &[u16], _order: ByteOrder, _decimals: usize) -> String {
+        regs.first()
+            .map(|v| format!("0x{:04X}", v))
+            .unwrap_or("-".into())
+    }
+
+    fn encode(&self, text: &str, _order: ByteOrder) -> Option<Vec<u16>> {
+        let hex = text.trim_start_matches("0x").trim_start_matches("0X");
+        u16::from_str_radix(hex, 16).ok().map(|v| vec![v])
+    }
+
+    fn register_count(&self) -> usize {
+        1
+    }
+}
+
+// two's complement next to its raw hex: "-2 (0xFFFE)" or "0xFFFE (-2)"
+pub struct SignedHex {
+    pub hex_first: bool,
+}
+
+impl Decoder for SignedHex {
+    fn decode(&self, regs: &[u16], _order: ByteOrder, _decimals: usize) -> String {
+        regs.first()
+            .map(|v| {
+                if self.hex_first {
+                    format!("0x{:04X} ({})", v, *v as i16)
+                } else {
+                    format!("{} (0x{:04X})", *v as i16, v)
+                }
+            })
+            .unwrap_or("-".into())
+    }
+
+    fn register_count(&self) -> usize {
+        1
+    }
+}
+
+pub struct Binary {
+    pub registers: usize,
+}
+
+impl Decoder for Binary {
+    fn decode(&self, regs: &[u16], order: ByteOrder, _decimals: usize) -> String {
+        // a lone register keeps its natural bit order
+        let words = if self.registers == 1 {
+            regs.first().map(|v| vec![*v])
+        } else {
+            words(regs, self.registers, order)
+        };
+
+        words
+            .map(|w| w.iter().map(|v| format!("{:016b}", v)).collect())
+            .unwrap_or("-".into())
+    }
+
+    fn register_count(&self) -> usize {
+        self.registers
+    }
+}
+
+pub struct Long;
+
+impl Decoder for Long {
+    fn decode(&self, regs: &[u16], order: ByteOrder, _decimals: usize) -> String {
+        words(regs, 2, order)
+            .map(|w| (to_u32(&w) as i32).to_string())
+            .unwrap_or("-".into())
+    }
+
+    fn value(&self, regs: &[u16], order: ByteOrder) -> Option<f64> {
+        words(regs, 2, order).map(|w| to_u32(&w) as i32 as f64)
+    }
+
+    fn encode(&self, text: &str, order: ByteOrder) -> Option<Vec<u16>> {
+        let v = text.parse::<i32>().ok()? as u32;
+        Some(order.apply(&[(v >> 16) as u16, v as u16]))
+    }
+
+    fn register_count(&self) -> usize {
+        2
+    }
+}
+
+// energy counters and the like that never go negative
+pub struct ULong;
+
+impl Decoder for ULong {
+    fn decode(&self, regs: &[u16], order: ByteOrder, _decimals: usize) -> String {
+        words(regs, 2, order)
+            .map(|w| to_u32(&w).to_string())
+            .unwrap_or("-".into())
+    }
+
+    fn value(&self, regs: &[u16], order: ByteOrder) -> Option<f64> {
+        words(regs, 2, order).map(|w| to_u32(&w) as f64)
+    }
+
+    fn encode(&self, text: &str, order: ByteOrder) -> Option<Vec<u16>> {
+        let v = text.parse::<u32>().ok()?;
+        Some(order.apply(&[(v >> 16) as u16, v as u16]))
+    }
+
+    fn register_count(&self) -> usize {
+        2
+    }
+}
+
+// packed BCD, four decimal digits per register: 0x1234 -> 1234
+pub struct Bcd;
+
+fn bcd_digits(v: u16) -> Option<u16> {
+    (0..4).rev().try_fold(0, |acc, i| {
+        let digit = (v >> (i * 4)) & 0xF;
+        (digit <= 9).then_some(acc * 10 + digit)
+    })
+}
+
+impl Decoder for Bcd {
+    fn decode(&self, regs: &[u16], _order: ByteOrder, _decimals: usize) -> String {
+        regs.first()
+            .map(|v| match bcd_digits(*v) {
+                Some(n) => n.to_string(),
+                None => format!("0x{:04X} (not BCD)", v),
+            })
+            .unwrap_or("-".into())
+    }
+
+    fn value(&self, regs: &[u16], _order: ByteOrder) -> Option<f64> {
+        regs.first().and_then(|v| bcd_digits(*v)).map(|n| n as f64)
+    }
+
+    fn encode(&self, text: &str, _order: ByteOrder) -> Option<Vec<u16>> {
+        let n = text.parse::<u16>().ok().filter(|n| *n <= 9999)?;
+        let bcd = (0..4).fold(0, |acc, i| acc | ((n / 10u16.pow(i) % 10) << (i * 4)));
+        Some(vec![bcd])
+    }
+
+    fn register_count(&self) -> usize {
+        1
+    }
+
+    fn is_valid(&self, regs: &[u16], _order: ByteOrder) -> bool {
+        regs.first().is_none_or(|v| bcd_digits(*v).is_some())
+    }
+}
+
+pub struct Float;
+
+impl Decoder for Float {
+    fn decode(&self, regs: &[u16], order: ByteOrder, decimals: usize) -> String {
+        words(regs, 2, order)
+            .map(|w| float_text(f32::from_bits(to_u32(&w)) as f64, decimals))
+            .unwrap_or("-".into())
+    }
+
+    fn value(&self, regs: &[u16], order: ByteOrder) -> Option<f64> {
+        words(regs, 2, order)
+            .map(|w| f32::from_bits(to_u32(&w)) as f64)
+            .filter(|v| v.is_finite())
+    }
+
+    fn encode(&self, text: &str, order: ByteOrder) -> Option<Vec<u16>> {
+        let v = text.parse::<f32>().ok()?.to_bits();
+        Some(order.apply(&[(v >> 16) as u16, v as u16]))
+    }
+
+    fn register_count(&self) -> usize {
+        2
+    }
+
+    fn is_valid(&self, regs: &[u16], order: ByteOrder) -> bool {
+        words(regs, 2, order)
+            .map(|w| f32::from_bits(to_u32(&w)).is_finite())
+            .unwrap_or(true)
+    }
+}
+
+pub struct Double;
+
+impl Decoder for Double {
+    fn decode(&self, regs: &[u16], order: ByteOrder, decimals: usize) -> String {
+        words(regs, 4, order)
+            .map(|w| float_text(f64::from_bits(to_u64(&w)), decimals))
+            .unwrap_or("-".into())
+    }
+
+    fn value(&self, regs: &[u16], order: ByteOrder) -> Option<f64> {
+        words(regs, 4, order)
+            .map(|w| f64::from_bits(to_u64(&w)))
+            .filter(|v| v.is_finite())
+    }
+
+    fn encode(&self, text: &str, order: ByteOrder) -> Option<Vec<u16>> {
+        let v = text.parse::<f64>().ok()?.to_bits();
+        Some(order.apply(&[
+            (v >> 48) as u16,
+            (v >> 32) as u16,
+            (v >> 16) as u16,
+            v as u16,
+        ]))
+    }
+
+    fn register_count(&self) -> usize {
+        4
+    }
+
+    fn is_valid(&self, regs: &[u16], order: ByteOrder) -> bool {
+        words(regs, 4, order)
+            .map(|w| f64::from_bits(to_u64(&w)).is_finite())
+            .unwrap_or(true)
+    }
+}
+
+// text packed two characters per register, high byte first unless `swapped`
+pub struct Ascii {
+    pub swapped: bool,
+}
+
+impl Decoder for Ascii {
+    fn decode(&self, regs: &[u16], _order: ByteOrder, _decimals: usize) -> String {
+        if regs.is_empty() {
+            return "-".into();
+        }
+        let bytes: Vec<u8> = regs
+            .iter()
+            .flat_map(|v| {
+                let [hi, lo] = v.to_be_bytes();
+                if self.swapped {
+                    [lo, hi]
+                } else {
+                    [hi, lo]
+                }
+            })
+            .collect();
+        bytes_to_ascii_dots(&bytes)
+    }
+
+    fn register_count(&self) -> usize {
+        0
+    }
+}
@@ -1,131 +1,509 @@
-#[derive(PartialEq, Clone, Copy, Debug)]
+use super::decode::{self, Decoder};
+use serde::de::{Deserializer, IntoDeserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+// Byte order of multi-register values, A being the most significant byte.
+// Single-register formats ignore it.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ByteOrder {
+    Abcd,
+    Dcba,
+    Badc,
+    Cdab,
+}
+
+impl ByteOrder {
+    pub const ALL: [ByteOrder; 4] = [
+        ByteOrder::Abcd,
+        ByteOrder::Dcba,
+        ByteOrder::Badc,
+        ByteOrder::Cdab,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ByteOrder::Abcd => "ABCD",
+            ByteOrder::Dcba => "DCBA",
+            ByteOrder::Badc => "BADC",
+            ByteOrder::Cdab => "CDAB",
+        }
+    }
+
+    // ABCD -> DCBA -> BADC -> CDAB -> ABCD
+    pub fn next(&self) -> ByteOrder {
+        match self {
+            ByteOrder::Abcd => ByteOrder::Dcba,
+            ByteOrder::Dcba => ByteOrder::Badc,
+            ByteOrder::Badc => ByteOrder::Cdab,
+            ByteOrder::Cdab => ByteOrder::Abcd,
+        }
+    }
+
+    // the same bytes with the word order reversed on top, what the old
+    // *Inverse formats did to whatever order was set
+    pub fn reversed_words(&self) -> ByteOrder {
+        match self {
+            ByteOrder::Abcd => ByteOrder::Cdab,
+            ByteOrder::Cdab => ByteOrder::Abcd,
+            ByteOrder::Dcba => ByteOrder::Badc,
+            ByteOrder::Badc => ByteOrder::Dcba,
+        }
+    }
+
+    // reorder registers so the result reads big-endian (ABCD)
+    pub fn apply(&self, raw: &[u16]) -> Vec<u16> {
+        match self {
+            ByteOrder::Abcd => raw.to_vec(),
+            ByteOrder::Dcba => raw.iter().rev().map(|v| v.swap_bytes()).collect(),
+            ByteOrder::Badc => raw.iter().map(|v| v.swap_bytes()).collect(),
+            ByteOrder::Cdab => raw.iter().rev().copied().collect(),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum DisplayFormat {
     Signed,
+    SignedHex,
     Unsigned,
     Hex,
+    HexSigned,
     Binary,
+    Binary32,
+    Binary64,
     Long,
-    LongInverse,
+    ULong,
     Float,
-    FloatInverse,
     Double,
-    DoubleInverse,
+    Ascii,
+    AsciiSwapped,
+    Bcd,
+}
+
+// A format as saved in a file. Files from before the byte order setting may
+// name LongInverse / FloatInverse / DoubleInverse: the plain format with its
+// word order reversed, which whoever loads it has to fold into the order it
+// decodes with. Saved again it is just the format.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StoredFormat {
+    pub format: DisplayFormat,
+    pub inverse: bool,
+}
+
+impl From<DisplayFormat> for StoredFormat {
+    fn from(format: DisplayFormat) -> Self {
+        StoredFormat {
+            format,
+            inverse: false,
+        }
+    }
+}
+
+impl Serialize for StoredFormat {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.format.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for StoredFormat {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        let (name, inverse) = match name.strip_suffix("Inverse") {
+            Some(base @ ("Long" | "Float" | "Double")) => (base, true),
+            _ => (name.as_str(), false),
+        };
+        let format = DisplayFormat::deserialize(name.into_deserializer())?;
+        Ok(StoredFormat { format, inverse })
+    }
 }
 
 impl DisplayFormat {
     pub fn label(&self) -> &'static str {
         match self {
             DisplayFormat::Signed => "Signed",
+            DisplayFormat::SignedHex => "Signed (Hex)",
             DisplayFormat::Unsigned => "Unsigned",
             DisplayFormat::Hex => "Hex",
+            DisplayFormat::HexSigned => "Hex (Signed)",
             DisplayFormat::Binary => "Binary",
+            DisplayFormat::Binary32 => "Binary 32",
+            DisplayFormat::Binary64 => "Binary 64",
             DisplayFormat::Long => "Long",
-            DisplayFormat::LongInverse => "Long Inverse",
+            DisplayFormat::ULong => "Unsigned Long",
             DisplayFormat::Float => "Float",
-            DisplayFormat::FloatInverse => "Float Inverse",
             DisplayFormat::Double => "Double",
-            DisplayFormat::DoubleInverse => "Double Inverse",
+            DisplayFormat::Ascii => "ASCII",
+            DisplayFormat::AsciiSwapped => "ASCII Swapped",
+            DisplayFormat::Bcd => "BCD",
         }
     }
 
-    pub const ALL: [DisplayFormat; 10] = [
+    pub const ALL: [DisplayFormat; 15] = [
         DisplayFormat::Signed,
+        DisplayFormat::SignedHex,
         DisplayFormat::Unsigned,
         DisplayFormat::Hex,
+        DisplayFormat::HexSigned,
         DisplayFormat::Binary,
+        DisplayFormat::Binary32,
+        DisplayFormat::Binary64,
         DisplayFormat::Long,
-        DisplayFormat::LongInverse,
+        DisplayFormat::ULong,
         DisplayFormat::Float,
-        DisplayFormat::FloatInverse,
         DisplayFormat::Double,
-        DisplayFormat::DoubleInverse,
+        DisplayFormat::Ascii,
+        DisplayFormat::AsciiSwapped,
+        DisplayFormat::Bcd,
     ];
 
-    pub fn register_count(&self) -> usize {
+    pub fn decoder(&self) -> &'static dyn Decoder {
         match self {
+            DisplayFormat::Signed => &decode::Signed,
+            DisplayFormat::SignedHex => &decode::SignedHex { hex_first: false },
+            DisplayFormat::Unsigned => &decode::Unsigned,
+            DisplayFormat::Hex => &decode::Hex,
+            DisplayFormat::HexSigned => &decode::SignedHex { hex_first: true },
+            DisplayFormat::Binary => &decode::Binary { registers: 1 },
+            DisplayFormat::Binary32 => &decode::Binary { registers: 2 },
+            DisplayFormat::Binary64 => &decode::Binary { registers: 4 },
+            DisplayFormat::Long => &decode::Long,
+            DisplayFormat::ULong => &decode::ULong,
+            DisplayFormat::Float => &decode::Float,
+            DisplayFormat::Double => &decode::Double,
+            DisplayFormat::Ascii => &decode::Ascii { swapped: false },
+            DisplayFormat::AsciiSwapped => &decode::Ascii { swapped: true },
+            DisplayFormat::Bcd => &decode::Bcd,
+        }
+    }
+
+    pub fn register_count(&self) -> usize {
+        self.decoder().register_count()
+    }
+
+    // registers one table row takes when `available` are left in the read
+    pub fn row_span(&self, available: usize) -> usize {
+        match self.register_count() {
+            0 => available.max(1),
+            n => n,
+        }
+    }
+
+    pub fn is_binary(&self) -> bool {
+        matches!(
+            self,
+            DisplayFormat::Binary | DisplayFormat::Binary32 | DisplayFormat::Binary64
+        )
+    }
+
+    pub fn is_valid(&self, raw: &[u16], order: ByteOrder) -> bool {
+        self.decoder().is_valid(raw, order)
+    }
+
+    pub fn format(&self, raw: &[u16], order: ByteOrder, decimals: usize) -> String {
+        self.decoder().decode(raw, order, decimals)
+    }
+
+    pub fn value(&self, raw: &[u16], order: ByteOrder) -> Option<f64> {
+        self.decoder().value(raw, order)
+    }
+
+    pub fn encode(&self, text: &str, order: ByteOrder) -> Option<Vec<u16>> {
+        self.decoder().encode(text.trim(), order)
+    }
+
+    pub fn is_editable(&self) -> bool {
+        matches!(
+            self,
             DisplayFormat::Signed
-            | DisplayFormat::Unsigned
-            | DisplayFormat::Hex
-            | DisplayFormat::Binary => 1,
+                | DisplayFormat::Unsigned
+                | DisplayFormat::Hex
+                | DisplayFormat::Long
+                | DisplayFormat::ULong
+                | DisplayFormat::Bcd
+                | DisplayFormat::Float
+                | DisplayFormat::Double
+        )
+    }
+}
+
+// The same registers read several ways at once, for the table's inspector
+// tooltip: each register on its own, then the wider formats the row fits.
+pub fn inspect(raw: &[u16], order: ByteOrder, decimals: usize) -> Vec<(&'static str, String)> {
+    let each = |format: DisplayFormat| {
+        raw.iter()
+            .map(|r| format.format(&[*r], order, decimals))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    };
+    let mut lines = vec![
+        ("Hex", each(DisplayFormat::Hex)),
+        (
+            "Binary",
+            raw.iter()
+                .map(|r| group_bits(&DisplayFormat::Binary.format(&[*r], order, decimals)))
+                .collect::<Vec<_>>()
+                .join(" | "),
+        ),
+        ("Unsigned", each(DisplayFormat::Unsigned)),
+        ("Signed", each(DisplayFormat::Signed)),
+    ];
+    let wide: &[DisplayFormat] = match raw.len() {
+        2 => &[
+            DisplayFormat::Long,
+            DisplayFormat::ULong,
+            DisplayFormat::Float,
+        ],
+        4 => &[DisplayFormat::Double],
+        _ => &[],
+    };
+    for format in wide {
+        lines.push((format.label(), format.format(raw, order, decimals)));
+    }
+    lines
+}
+
+// upper bound of the Float / Double precision setting
+pub const MAX_DECIMALS: usize = 10;
+
+// 4 decimals at most, trailing zeros dropped: 23.5, 100, -0.125
+pub fn format_number(v: f64) -> String {
+    let text = format!("{:.4}", v);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// "0000000100100011" -> "0000 0001 0010 0011"
+pub fn group_bits(bits: &str) -> String {
+    bits.as_bytes()
+        .chunks(4)
+        .map(|c| String::from_utf8_lossy(c).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// index of the highest bit of each nibble, aligned with the (grouped) bit string
+pub fn bit_ruler(bits: usize, grouped: bool) -> String {
+    let width = if grouped { 5 } else { 4 };
+    (0..bits / 4)
+        .map(|g| format!("{:<width$}", bits - 1 - g * 4, width = width))
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
 
-            DisplayFormat::Long
-            | DisplayFormat::LongInverse
-            | DisplayFormat::Float
-            | DisplayFormat::FloatInverse => 2,
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            DisplayFormat::Double | DisplayFormat::DoubleInverse => 4,
+    // -123.456f32 = 0xC2F6E979, bytes A..D = C2 F6 E9 79
+    const FLOAT: [(ByteOrder, [u16; 2]); 4] = [
+        (ByteOrder::Abcd, [0xC2F6, 0xE979]),
+        (ByteOrder::Dcba, [0x79E9, 0xF6C2]),
+        (ByteOrder::Badc, [0xF6C2, 0x79E9]),
+        (ByteOrder::Cdab, [0xE979, 0xC2F6]),
+    ];
+
+    #[test]
+    fn float_in_every_byte_order() {
+        for (order, raw) in FLOAT {
+            assert_eq!(
+                DisplayFormat::Float.format(&raw, order, 4),
+                "-123.4560",
+                "{:?}",
+                order
+            );
         }
     }
 
-    pub fn format(&self, raw: &[u16]) -> String {
-        match self {
-            DisplayFormat::Signed => raw
-                .get(0)
-                .map(|v| (*v as i16).to_string())
-                .unwrap_or("-".into()),
-            DisplayFormat::Unsigned => raw.get(0).map(|v| v.to_string()).unwrap_or("-".into()),
-            DisplayFormat::Hex => raw
-                .get(0)
-                .map(|v| format!("0x{:04X}", v))
-                .unwrap_or("-".into()),
-            DisplayFormat::Binary => raw
-                .get(0)
-                .map(|v| format!("{:016b}", v))
-                .unwrap_or("-".into()),
-            DisplayFormat::Long => {
-                if raw.len() >= 2 {
-                    let v = ((raw[0] as u32) << 16) | raw[1] as u32;
-                    (v as i32).to_string()
-                } else {
-                    "-".into()
-                }
-            }
-            DisplayFormat::LongInverse => {
-                if raw.len() >= 2 {
-                    let v = ((raw[1] as u32) << 16) | raw[0] as u32;
-                    (v as i32).to_string()
-                } else {
-                    "-".into()
-                }
-            }
-            DisplayFormat::Float => {
-                if raw.len() >= 2 {
-                    let bits = ((raw[0] as u32) << 16) | raw[1] as u32;
-                    format!("{:.4}", f32::from_bits(bits))
-                } else {
-                    "-".into()
-                }
-            }
-            DisplayFormat::FloatInverse => {
-                if raw.len() >= 2 {
-                    let bits = ((raw[1] as u32) << 16) | raw[0] as u32;
-                    format!("{:.4}", f32::from_bits(bits))
-                } else {
-                    "-".into()
-                }
-            }
-            DisplayFormat::Double => {
-                if raw.len() >= 4 {
-                    let bits = ((raw[0] as u64) << 48)
-                        | ((raw[1] as u64) << 32)
-                        | ((raw[2] as u64) << 16)
-                        | (raw[3] as u64);
-                    format!("{:.4}", f64::from_bits(bits))
-                } else {
-                    "-".into()
-                }
-            }
-            DisplayFormat::DoubleInverse => {
-                if raw.len() >= 4 {
-                    let bits = ((raw[3] as u64) << 48)
-                        | ((raw[2] as u64) << 32)
-                        | ((raw[1] as u64) << 16)
-                        | (raw[0] as u64);
-                    format!("{:.4}", f64::from_bits(bits))
-                } else {
-                    "-".into()
-                }
-            }
+    #[test]
+    fn float_precision_follows_decimals() {
+        let (order, raw) = FLOAT[0];
+        assert_eq!(DisplayFormat::Float.format(&raw, order, 2), "-123.46");
+        assert_eq!(DisplayFormat::Float.format(&raw, order, 0), "-123");
+        // 23.4f32 is 23.399999618530273 in full
+        let raw = DisplayFormat::Float.encode("23.4", order).unwrap();
+        assert_eq!(DisplayFormat::Float.format(&raw, order, 3), "23.400");
+        // integer formats are unaffected
+        assert_eq!(DisplayFormat::Long.format(&raw, order, 0), "1102787379");
+    }
+
+    #[test]
+    fn non_finite_floats_are_spelled_out() {
+        let order = ByteOrder::Abcd;
+        let nan = [0x7FC0, 0x0000];
+        let inf = [0x7F80, 0x0000];
+        let neg_inf = [0xFF80, 0x0000];
+        assert_eq!(DisplayFormat::Float.format(&nan, order, 3), "NaN");
+        assert_eq!(DisplayFormat::Float.format(&inf, order, 3), "+Inf");
+        assert_eq!(DisplayFormat::Float.format(&neg_inf, order, 3), "-Inf");
+        for raw in [nan, inf, neg_inf] {
+            assert!(!DisplayFormat::Float.is_valid(&raw, order));
+            assert_eq!(DisplayFormat::Float.value(&raw, order), None);
+        }
+
+        let nan = [0x7FF8, 0, 0, 0];
+        assert_eq!(DisplayFormat::Double.format(&nan, order, 3), "NaN");
+        assert!(!DisplayFormat::Double.is_valid(&nan, order));
+    }
+
+    #[test]
+    fn inspect_shows_every_reading() {
+        let lines = inspect(&[0xC2F6, 0xE979], ByteOrder::Abcd, 2);
+        let get = |label: &str| {
+            lines
+                .iter()
+                .find(|(l, _)| *l == label)
+                .map(|(_, v)| v.as_str())
+                .unwrap()
+        };
+        assert_eq!(get("Unsigned"), "49910 | 59769");
+        assert_eq!(get("Signed"), "-15626 | -5767");
+        assert_eq!(get("Binary"), "1100 0010 1111 0110 | 1110 1001 0111 1001");
+        assert_eq!(get("Float"), "-123.46");
+        assert_eq!(get("Unsigned Long"), "3270961529");
+
+        // a single register has no wide readings
+        assert_eq!(inspect(&[1], ByteOrder::Abcd, 2).len(), 4);
+    }
+
+    #[test]
+    fn long_in_every_byte_order() {
+        // 0xC2F6E979 as i32
+        for (order, raw) in FLOAT {
+            assert_eq!(
+                DisplayFormat::Long.format(&raw, order, 4),
+                "-1024005767",
+                "{:?}",
+                order
+            );
+        }
+    }
+
+    #[test]
+    fn ulong_energy_counter_in_every_byte_order() {
+        // 123456789 Wh = 0x075BCD15
+        let counter = [
+            (ByteOrder::Abcd, [0x075B, 0xCD15]),
+            (ByteOrder::Dcba, [0x15CD, 0x5B07]),
+            (ByteOrder::Badc, [0x5B07, 0x15CD]),
+            (ByteOrder::Cdab, [0xCD15, 0x075B]),
+        ];
+        for (order, raw) in counter {
+            assert_eq!(
+                DisplayFormat::ULong.format(&raw, order, 4),
+                "123456789",
+                "{:?}",
+                order
+            );
+            assert_eq!(
+                DisplayFormat::ULong.encode("123456789", order),
+                Some(raw.to_vec())
+            );
+        }
+        // past i32::MAX, where Long goes negative
+        for (order, raw) in FLOAT {
+            assert_eq!(DisplayFormat::ULong.format(&raw, order, 4), "3270961529");
+        }
+    }
+
+    #[test]
+    fn bcd_reads_four_digits_per_register() {
+        assert_eq!(
+            DisplayFormat::Bcd.format(&[0x1234], ByteOrder::Abcd, 4),
+            "1234"
+        );
+        // meter date 2024-09-15 as three BCD registers
+        let date: Vec<String> = [0x2024, 0x0009, 0x0015]
+            .iter()
+            .map(|r| DisplayFormat::Bcd.format(&[*r], ByteOrder::Abcd, 4))
+            .collect();
+        assert_eq!(date, ["2024", "9", "15"]);
+        assert_eq!(
+            DisplayFormat::Bcd.value(&[0x0950], ByteOrder::Abcd),
+            Some(950.0)
+        );
+        assert_eq!(
+            DisplayFormat::Bcd.encode("950", ByteOrder::Abcd),
+            Some(vec![0x0950])
+        );
+    }
+
+    #[test]
+    fn bcd_flags_nibbles_above_nine() {
+        assert_eq!(
+            DisplayFormat::Bcd.format(&[0x12A4], ByteOrder::Abcd, 4),
+            "0x12A4 (not BCD)"
+        );
+        assert!(!DisplayFormat::Bcd.is_valid(&[0x12A4], ByteOrder::Abcd));
+        assert_eq!(DisplayFormat::Bcd.encode("10000", ByteOrder::Abcd), None);
+    }
+
+    #[test]
+    fn single_register_formats_ignore_order() {
+        for order in ByteOrder::ALL {
+            assert_eq!(DisplayFormat::Signed.format(&[0xFFFE], order, 4), "-2");
+            assert_eq!(DisplayFormat::Unsigned.format(&[0x1234], order, 4), "4660");
+            assert_eq!(DisplayFormat::Hex.format(&[0x1234], order, 4), "0x1234");
+        }
+    }
+
+    #[test]
+    fn ascii_packs_two_chars_per_register() {
+        let raw = [0x4142, 0x4300, 0x3132];
+        assert_eq!(
+            DisplayFormat::Ascii.format(&raw, ByteOrder::Abcd, 4),
+            "ABC.12"
+        );
+        assert_eq!(
+            DisplayFormat::AsciiSwapped.format(&raw, ByteOrder::Abcd, 4),
+            "BA.C21"
+        );
+    }
+
+    #[test]
+    fn encode_round_trips_in_every_byte_order() {
+        for (order, raw) in FLOAT {
+            assert_eq!(
+                DisplayFormat::Float.encode("-123.456", order),
+                Some(raw.to_vec())
+            );
+            assert_eq!(
+                DisplayFormat::Long.encode("-1024005767", order),
+                Some(raw.to_vec())
+            );
+        }
+        assert_eq!(
+            DisplayFormat::Signed.encode("-2", ByteOrder::Abcd),
+            Some(vec![0xFFFE])
+        );
+        assert_eq!(DisplayFormat::Signed.encode("40000", ByteOrder::Abcd), None);
+    }
+
+    #[test]
+    fn legacy_inverse_formats_reverse_the_words() {
+        let stored: StoredFormat = serde_json::from_str("\"FloatInverse\"").unwrap();
+        assert_eq!(stored.format, DisplayFormat::Float);
+        assert!(stored.inverse);
+
+        // what FloatInverse showed for these registers under ABCD
+        let order = ByteOrder::Abcd.reversed_words();
+        assert_eq!(
+            stored.format.format(&[0xE979, 0xC2F6], order, 3),
+            "-123.456"
+        );
+        assert_eq!(serde_json::to_string(&stored).unwrap(), "\"Float\"");
+
+        let stored: StoredFormat = serde_json::from_str("\"Long\"").unwrap();
+        assert!(!stored.inverse);
+        assert!(serde_json::from_str::<StoredFormat>("\"SignedInverse\"").is_err());
+    }
+
+    #[test]
+    fn reversed_words_matches_the_old_inverse() {
+        let raw = [0x1122, 0x3344];
+        for order in ByteOrder::ALL {
+            let mut words = order.apply(&raw);
+            words.reverse();
+            assert_eq!(order.reversed_words().apply(&raw), words);
         }
     }
 }
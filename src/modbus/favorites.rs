@@ -0,0 +1,79 @@
+use super::app::ModbusFunction;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const FAVORITES_FILE: &str = "modbus_favorites.json";
+
+// a bookmarked read: same device, different view
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Favorite {
+    pub name: String,
+    pub function: ModbusFunction,
+    pub address: u16,
+    pub quantity: u16,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Favorites {
+    #[serde(default)]
+    pub items: Vec<Favorite>,
+    // why the file couldn't be read; save leaves it alone while this is set
+    #[serde(skip)]
+    pub load_error: Option<String>,
+}
+
+impl Favorites {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    // missing file is an empty list; an unreadable one too, with the reason
+    // in load_error
+    pub fn load_or_default(path: &str) -> Self {
+        if !Path::new(path).exists() {
+            return Favorites::default();
+        }
+        Self::load(path).unwrap_or_else(|e| Favorites {
+            load_error: Some(format!("Load {} failed: {}", path, e)),
+            ..Favorites::default()
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        if self.load_error.is_some() {
+            bail!("{} could not be read, Reload or Overwrite it first", path);
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unreadable_favorites_are_reported_and_not_overwritten() {
+        let dir = std::env::temp_dir().join(format!("favorites-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("favorites.json");
+        let path = path.to_str().unwrap();
+        fs::write(path, "{ \"items\": [").unwrap();
+
+        let mut favorites = Favorites::load_or_default(path);
+        assert!(favorites.load_error.is_some());
+        favorites.items.push(Favorite {
+            name: "Setpoints".into(),
+            function: ModbusFunction::ReadHolding,
+            address: 100,
+            quantity: 4,
+        });
+        assert!(favorites.save(path).is_err());
+        assert_eq!(fs::read_to_string(path).unwrap(), "{ \"items\": [");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
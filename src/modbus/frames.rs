@@ -0,0 +1,159 @@
+use crate::message::AppMessage;
+use crate::serial::utils::{bytes_to_hex_string, now_timestamp};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+// Hands a copy of every byte written to / read from the transport to the UI,
+// so the log shows the frames as they are on the wire (MBAP header or CRC
+// included). Costs nothing but an atomic load while switched off.
+#[derive(Clone)]
+pub struct FrameTap {
+    enabled: Arc<AtomicBool>,
+    tx: Sender<AppMessage>,
+}
+
+impl FrameTap {
+    fn send(&self, sent: bool, bytes: &[u8]) {
+        if !bytes.is_empty() && self.enabled.load(Ordering::Relaxed) {
+            let _ = self.tx.send(AppMessage::ModbusFrame {
+                sent,
+                bytes: bytes.to_vec(),
+            });
+        }
+    }
+}
+
+// transport with an optional tap; without one it only passes through
+pub struct Tapped<S> {
+    inner: S,
+    tap: Option<FrameTap>,
+}
+
+impl<S> Tapped<S> {
+    pub fn new(inner: S, tap: Option<&FrameTap>) -> Self {
+        Tapped {
+            inner,
+            tap: tap.cloned(),
+        }
+    }
+
+    fn send(&self, sent: bool, bytes: &[u8]) {
+        if let Some(tap) = &self.tap {
+            tap.send(sent, bytes);
+        }
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Tapped<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for Tapped<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = result {
+            self.send(false, &buf.filled()[before..]);
+        }
+        result
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for Tapped<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let result = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = result {
+            self.send(true, &buf[..n]);
+        }
+        result
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+// The "Raw frames" pane. Off by default since every poll adds two lines.
+pub struct FrameLog {
+    enabled: Arc<AtomicBool>,
+    pub max_lines: usize,
+    lines: VecDeque<String>,
+}
+
+impl FrameLog {
+    pub fn new() -> Self {
+        FrameLog {
+            enabled: Arc::new(AtomicBool::new(false)),
+            max_lines: 2000,
+            lines: VecDeque::new(),
+        }
+    }
+
+    // the flag is shared, so toggling applies to an already open link
+    pub fn tap(&self, tx: Sender<AppMessage>) -> FrameTap {
+        FrameTap {
+            enabled: self.enabled.clone(),
+            tx,
+        }
+    }
+
+    pub fn push(&mut self, sent: bool, bytes: &[u8]) {
+        let dir = if sent { "TX" } else { "RX" };
+        self.lines.push_back(format!(
+            "[{}] {} {}",
+            now_timestamp(),
+            dir,
+            bytes_to_hex_string(bytes)
+        ));
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let mut enabled = self.enabled.load(Ordering::Relaxed);
+            if ui.checkbox(&mut enabled, "Log raw frames").changed() {
+                self.enabled.store(enabled, Ordering::Relaxed);
+            }
+            if ui.button("Clear").clicked() {
+                self.lines.clear();
+            }
+            ui.weak(format!("{} lines", self.lines.len()));
+        });
+
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt("raw_frames")
+                .max_height(160.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    ui.set_min_width(ui.available_width());
+                    for line in &self.lines {
+                        ui.label(egui::RichText::new(line).monospace());
+                    }
+                });
+        });
+    }
+}
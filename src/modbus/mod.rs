@@ -1,2 +1,13 @@
 pub mod app;
+pub mod config;
+pub mod decode;
 pub mod display;
+pub mod favorites;
+pub mod frames;
+pub mod register_map;
+#[cfg(feature = "link-sim")]
+pub mod sim;
+pub mod snapshot;
+pub mod sweep;
+pub mod tls;
+pub mod trend;
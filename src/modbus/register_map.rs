@@ -0,0 +1,530 @@
+use super::display::{ByteOrder, DisplayFormat, StoredFormat};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const REGISTER_MAP_FILE: &str = "register_map.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RegisterInfo {
+    // shown beside the address, e.g. Voltage
+    #[serde(default)]
+    pub name: String,
+    // raw value -> label, e.g. 0 = Off, 1 = Run, 2 = Fault
+    #[serde(default)]
+    pub lookup: BTreeMap<u16, String>,
+    // engineering unit shown after the value, e.g. °C, kPa, rpm
+    #[serde(default)]
+    pub unit: String,
+    // engineering value = decoded * scale + offset, numeric formats only
+    #[serde(default = "unit_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub offset: f64,
+}
+
+fn unit_scale() -> f64 {
+    1.0
+}
+
+impl Default for RegisterInfo {
+    fn default() -> Self {
+        RegisterInfo {
+            name: String::new(),
+            lookup: BTreeMap::new(),
+            unit: String::new(),
+            scale: 1.0,
+            offset: 0.0,
+        }
+    }
+}
+
+impl RegisterInfo {
+    fn is_scaled(&self) -> bool {
+        self.scale != 1.0 || self.offset != 0.0
+    }
+}
+
+// A 32/64-bit value whose words are not adjacent (some power meters put all
+// high words first, then all low words).
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(from = "StoredMappedValue")]
+pub struct MappedValue {
+    pub name: String,
+    // source addresses, most significant word first
+    pub words: Vec<u16>,
+    pub format: DisplayFormat,
+}
+
+// a mapped value as saved; an old *Inverse format listed the words least
+// significant first
+#[derive(Deserialize)]
+struct StoredMappedValue {
+    name: String,
+    words: Vec<u16>,
+    format: StoredFormat,
+}
+
+impl From<StoredMappedValue> for MappedValue {
+    fn from(stored: StoredMappedValue) -> Self {
+        let mut words = stored.words;
+        if stored.format.inverse {
+            words.reverse();
+        }
+        MappedValue {
+            name: stored.name,
+            words,
+            format: stored.format.format,
+        }
+    }
+}
+
+impl MappedValue {
+    // None while any source address is outside the block that was read
+    pub fn decode(&self, start: u16, data: &[u16], decimals: usize) -> Option<String> {
+        let regs = self
+            .words
+            .iter()
+            .map(|addr| {
+                addr.checked_sub(start)
+                    .and_then(|i| data.get(i as usize).copied())
+            })
+            .collect::<Option<Vec<u16>>>()?;
+        // the address list already fixes the word order
+        Some(self.format.format(&regs, ByteOrder::Abcd, decimals))
+    }
+}
+
+// formats a mapped value can assemble
+pub const MAPPED_FORMATS: [DisplayFormat; 5] = [
+    DisplayFormat::Long,
+    DisplayFormat::Float,
+    DisplayFormat::Double,
+    DisplayFormat::Binary32,
+    DisplayFormat::Binary64,
+];
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct RegisterMap {
+    #[serde(default)]
+    pub registers: BTreeMap<u16, RegisterInfo>,
+    // added to displayed addresses so they match the vendor's numbering
+    #[serde(default)]
+    pub display_offset: i32,
+    #[serde(default)]
+    pub mapped: Vec<MappedValue>,
+    // why the file couldn't be read; save leaves it alone while this is set
+    #[serde(skip)]
+    pub load_error: Option<String>,
+}
+
+impl RegisterMap {
+    pub fn load(path: &str) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    // missing file is not an error, the map just starts empty; an unreadable
+    // one starts empty too, with the reason in load_error
+    pub fn load_or_default(path: &str) -> Self {
+        if !Path::new(path).exists() {
+            return RegisterMap::default();
+        }
+        Self::load(path).unwrap_or_else(|e| RegisterMap {
+            load_error: Some(format!("Load {} failed: {}", path, e)),
+            ..RegisterMap::default()
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        if self.load_error.is_some() {
+            bail!(
+                "{} could not be read, Reload or Overwrite it in the Register Map first",
+                path
+            );
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn lookup(&self, address: u16, value: u16) -> Option<&str> {
+        self.registers
+            .get(&address)
+            .and_then(|r| r.lookup.get(&value))
+            .map(|s| s.as_str())
+    }
+
+    pub fn name(&self, address: u16) -> Option<&str> {
+        self.registers
+            .get(&address)
+            .map(|r| r.name.as_str())
+            .filter(|n| !n.is_empty())
+    }
+
+    pub fn unit(&self, address: u16) -> Option<&str> {
+        self.registers
+            .get(&address)
+            .map(|r| r.unit.as_str())
+            .filter(|u| !u.is_empty())
+    }
+
+    // (scale, offset) when the register has a non-identity transform
+    pub fn scaling(&self, address: u16) -> Option<(f64, f64)> {
+        self.registers
+            .get(&address)
+            .filter(|r| r.is_scaled())
+            .map(|r| (r.scale, r.offset))
+    }
+
+    pub fn entry(&mut self, address: u16) -> &mut RegisterInfo {
+        self.registers.entry(address).or_default()
+    }
+
+    // drop registers that no longer carry any information
+    pub fn prune(&mut self) {
+        self.registers.retain(|_, r| {
+            !r.lookup.is_empty() || !r.unit.is_empty() || !r.name.is_empty() || r.is_scaled()
+        });
+    }
+}
+
+// "0=Off, 1=Run, 2=Fault" (comma or newline separated)
+pub fn parse_lookup(text: &str) -> Result<BTreeMap<u16, String>, String> {
+    let mut lookup = BTreeMap::new();
+
+    for item in text.split([',', '\n']) {
+        let item = item.trim();
+        if item.is_empty() {
+            continue;
+        }
+
+        let Some((value, label)) = item.split_once('=') else {
+            return Err(format!("Expected value=label, got '{}'", item));
+        };
+        let value: u16 = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid value '{}'", value.trim()))?;
+
+        lookup.insert(value, label.trim().to_string());
+    }
+
+    Ok(lookup)
+}
+
+// "100, 200" -> [100, 200]
+pub fn parse_words(text: &str, format: DisplayFormat) -> Result<Vec<u16>, String> {
+    let words = text
+        .split([',', ' '])
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|_| format!("Invalid address '{}'", s)))
+        .collect::<Result<Vec<u16>, String>>()?;
+
+    if words.len() != format.register_count() {
+        return Err(format!(
+            "{} needs {} addresses, got {}",
+            format.label(),
+            format.register_count(),
+            words.len()
+        ));
+    }
+    Ok(words)
+}
+
+pub fn lookup_to_text(lookup: &BTreeMap<u16, String>) -> String {
+    lookup
+        .iter()
+        .map(|(v, l)| format!("{}={}", v, l))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub struct RegisterMapEditor {
+    pub open: bool,
+    pub address: u16,
+    pub lookup_text: String,
+    pub name: String,
+    pub unit: String,
+    pub scale: f64,
+    pub offset: f64,
+    pub status: String,
+
+    pub mapped_name: String,
+    pub mapped_words: String,
+    pub mapped_format: DisplayFormat,
+}
+
+impl RegisterMapEditor {
+    pub fn new() -> Self {
+        RegisterMapEditor {
+            open: false,
+            address: 0,
+            lookup_text: String::new(),
+            name: String::new(),
+            unit: String::new(),
+            scale: 1.0,
+            offset: 0.0,
+            status: String::new(),
+
+            mapped_name: String::new(),
+            mapped_words: String::new(),
+            mapped_format: DisplayFormat::Long,
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context, map: &mut RegisterMap) {
+        let mut open = self.open;
+
+        egui::Window::new("Register Map")
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Address");
+                    if ui.add(egui::DragValue::new(&mut self.address)).changed() {
+                        self.select(map, self.address);
+                    }
+
+                    ui.label("Name");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.name)
+                            .hint_text("Voltage")
+                            .desired_width(100.0),
+                    );
+
+                    ui.label("Unit");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.unit)
+                            .hint_text("°C")
+                            .desired_width(60.0),
+                    );
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Scale");
+                    ui.add(egui::DragValue::new(&mut self.scale).speed(0.01));
+                    ui.label("Offset");
+                    ui.add(egui::DragValue::new(&mut self.offset).speed(0.1));
+                    ui.weak("value × scale + offset");
+                });
+
+                ui.label("Lookup (value=label, comma separated)");
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.lookup_text)
+                        .hint_text("0=Off, 1=Run, 2=Fault")
+                        .desired_rows(3),
+                );
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(egui::RichText::new("Apply").color(egui::Color32::BLUE))
+                        .clicked()
+                    {
+                        match parse_lookup(&self.lookup_text) {
+                            Ok(lookup) => {
+                                let entry = map.entry(self.address);
+                                entry.lookup = lookup;
+                                entry.name = self.name.trim().to_string();
+                                entry.unit = self.unit.trim().to_string();
+                                entry.scale = self.scale;
+                                entry.offset = self.offset;
+                                map.prune();
+                                self.save(map);
+                            }
+                            Err(e) => self.status = e,
+                        }
+                    }
+
+                    if ui.button("Reload").clicked() {
+                        match RegisterMap::load(REGISTER_MAP_FILE) {
+                            Ok(loaded) => {
+                                *map = loaded;
+                                self.status = format!("Loaded {}", REGISTER_MAP_FILE);
+                            }
+                            Err(e) => self.status = format!("Load failed: {}", e),
+                        }
+                    }
+                });
+
+                ui.monospace(&self.status);
+
+                if let Some(error) = &map.load_error {
+                    let mut overwrite = false;
+                    ui.horizontal_wrapped(|ui| {
+                        ui.colored_label(ui.visuals().error_fg_color, format!("✖ {}", error));
+                        overwrite = ui
+                            .button("Overwrite")
+                            .on_hover_text("Replace the file with the map shown here")
+                            .clicked();
+                    });
+                    if overwrite {
+                        map.load_error = None;
+                        self.save(map);
+                    }
+                }
+
+                ui.separator();
+
+                egui::Grid::new("register_map_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Address");
+                        ui.label("Name");
+                        ui.label("Unit");
+                        ui.label("Scale");
+                        ui.label("Lookup");
+                        ui.end_row();
+
+                        let mut selected = None;
+                        for (addr, info) in &map.registers {
+                            if ui.link(addr.to_string()).clicked() {
+                                selected = Some(*addr);
+                            }
+                            ui.label(&info.name);
+                            ui.label(&info.unit);
+                            ui.label(if info.is_scaled() {
+                                format!("×{} {:+}", info.scale, info.offset)
+                            } else {
+                                String::new()
+                            });
+                            ui.label(lookup_to_text(&info.lookup));
+                            ui.end_row();
+                        }
+                        if let Some(addr) = selected {
+                            self.select(map, addr);
+                        }
+                    });
+
+                ui.separator();
+
+                egui::CollapsingHeader::new("Split 32/64-bit values").show(ui, |ui| {
+                    self.ui_mapped(ui, map);
+                });
+            });
+
+        self.open = open;
+    }
+
+    fn ui_mapped(&mut self, ui: &mut egui::Ui, map: &mut RegisterMap) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.mapped_name)
+                    .hint_text("name")
+                    .desired_width(90.0),
+            );
+            ui.add(
+                egui::TextEdit::singleline(&mut self.mapped_words)
+                    .hint_text("high, low: 100, 200")
+                    .desired_width(120.0),
+            );
+            egui::ComboBox::from_id_salt("mapped_format")
+                .selected_text(self.mapped_format.label())
+                .show_ui(ui, |ui| {
+                    for format in MAPPED_FORMATS {
+                        ui.selectable_value(&mut self.mapped_format, format, format.label());
+                    }
+                });
+
+            if ui.button("Add").clicked() {
+                match parse_words(&self.mapped_words, self.mapped_format) {
+                    Ok(words) => {
+                        map.mapped.push(MappedValue {
+                            name: self.mapped_name.trim().to_string(),
+                            words,
+                            format: self.mapped_format,
+                        });
+                        self.mapped_name.clear();
+                        self.mapped_words.clear();
+                        self.save(map);
+                    }
+                    Err(e) => self.status = e,
+                }
+            }
+        });
+
+        let mut remove = None;
+        egui::Grid::new("mapped_values_grid")
+            .striped(true)
+            .show(ui, |ui| {
+                for (i, value) in map.mapped.iter().enumerate() {
+                    ui.label(&value.name);
+                    ui.label(
+                        value
+                            .words
+                            .iter()
+                            .map(|a| a.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    );
+                    ui.label(value.format.label());
+                    if ui.small_button("✖").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if let Some(i) = remove {
+            map.mapped.remove(i);
+            self.save(map);
+        }
+    }
+
+    fn save(&mut self, map: &RegisterMap) {
+        self.status = match map.save(REGISTER_MAP_FILE) {
+            Ok(_) => format!("Saved to {}", REGISTER_MAP_FILE),
+            Err(e) => format!("Save failed: {}", e),
+        };
+    }
+
+    fn select(&mut self, map: &RegisterMap, address: u16) {
+        self.address = address;
+        let info = map.registers.get(&address);
+        self.lookup_text = info.map(|r| lookup_to_text(&r.lookup)).unwrap_or_default();
+        self.name = info.map(|r| r.name.clone()).unwrap_or_default();
+        self.unit = info.map(|r| r.unit.clone()).unwrap_or_default();
+        self.scale = info.map(|r| r.scale).unwrap_or(1.0);
+        self.offset = info.map(|r| r.offset).unwrap_or(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_inverse_mapped_value_keeps_its_value() {
+        let text = r#"{ "name": "Power", "words": [10, 11], "format": "FloatInverse" }"#;
+        let value: MappedValue = serde_json::from_str(text).unwrap();
+
+        assert_eq!(value.format, DisplayFormat::Float);
+        assert_eq!(value.words, [11, 10]);
+        assert_eq!(
+            value.decode(10, &[0xE979, 0xC2F6], 3).as_deref(),
+            Some("-123.456")
+        );
+    }
+
+    #[test]
+    fn unreadable_map_is_reported_and_not_overwritten() {
+        let dir = std::env::temp_dir().join(format!("register-map-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("register_map.json");
+        let path = path.to_str().unwrap();
+        fs::write(path, "{ \"registers\": ").unwrap();
+
+        let mut map = RegisterMap::load_or_default(path);
+        assert!(map.load_error.is_some());
+        map.entry(1).name = "Voltage".into();
+        assert!(map.save(path).is_err());
+        assert_eq!(fs::read_to_string(path).unwrap(), "{ \"registers\": ");
+
+        map.load_error = None;
+        map.save(path).unwrap();
+        assert_eq!(RegisterMap::load(path).unwrap().name(1), Some("Voltage"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
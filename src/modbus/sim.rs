@@ -0,0 +1,99 @@
+// Degraded-link simulation for exercising the error handling UI without
+// touching real hardware. Only compiled with `--features link-sim`.
+use anyhow::{Error, Result};
+use std::future::{self, Future};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy)]
+pub struct LinkSim {
+    pub enabled: bool,
+    pub drop_pct: u8,
+    pub delay_pct: u8,
+    pub delay_ms: u64,
+    pub corrupt_pct: u8,
+}
+
+impl LinkSim {
+    pub fn new() -> Self {
+        LinkSim {
+            enabled: false,
+            drop_pct: 10,
+            delay_pct: 10,
+            delay_ms: 500,
+            corrupt_pct: 10,
+        }
+    }
+
+    // wraps one request: the delay and a lost answer count against the
+    // caller's timeout, corruption flips a bit of what did come back
+    pub async fn apply(
+        self,
+        request: impl Future<Output = Result<Vec<u16>, Error>>,
+    ) -> Result<Vec<u16>, Error> {
+        if !self.enabled {
+            return request.await;
+        }
+
+        let mut rng = XorShift::seeded();
+
+        if rng.chance(self.delay_pct) {
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+        }
+
+        let mut data = request.await?;
+        if rng.chance(self.drop_pct) {
+            // sent and answered, but the answer never arrives
+            return future::pending().await;
+        }
+        if !data.is_empty() && rng.chance(self.corrupt_pct) {
+            let i = rng.next() as usize % data.len();
+            data[i] ^= 1 << (rng.next() % 16);
+        }
+
+        Ok(data)
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Link simulation (test)").show(ui, |ui| {
+            ui.checkbox(&mut self.enabled, "Enabled");
+
+            ui.horizontal(|ui| {
+                ui.label("Drop %");
+                ui.add(egui::DragValue::new(&mut self.drop_pct).range(0..=100));
+
+                ui.label("Delay %");
+                ui.add(egui::DragValue::new(&mut self.delay_pct).range(0..=100));
+
+                ui.label("Delay ms");
+                ui.add(egui::DragValue::new(&mut self.delay_ms).range(0..=60000));
+
+                ui.label("Corrupt %");
+                ui.add(egui::DragValue::new(&mut self.corrupt_pct).range(0..=100));
+            });
+        });
+    }
+}
+
+// good enough randomness for fault injection, no extra dependency
+struct XorShift(u64);
+
+impl XorShift {
+    fn seeded() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x9E37_79B9_7F4A_7C15);
+        XorShift(nanos | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn chance(&mut self, pct: u8) -> bool {
+        (self.next() % 100) < pct as u64
+    }
+}
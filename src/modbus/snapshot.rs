@@ -0,0 +1,42 @@
+use super::app::{ModbusFunction, ModbusMode};
+use super::display::{ByteOrder, DisplayFormat};
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+
+pub const SNAPSHOT_FILE: &str = "modbus_snapshot.json";
+
+// What the table shows right now, for scripts to diff or assert on. Values
+// are decoded with the active format, byte order and scaling.
+#[derive(Serialize)]
+pub struct Snapshot {
+    pub taken_at: String,
+    pub mode: ModbusMode,
+    // "192.168.1.10:502" or "COM3 @ 9600"
+    pub endpoint: String,
+    pub slave_id: u8,
+    pub function: ModbusFunction,
+    pub address: u16,
+    pub quantity: u16,
+    pub byte_order: ByteOrder,
+    pub registers: Vec<SnapshotRow>,
+}
+
+#[derive(Serialize)]
+pub struct SnapshotRow {
+    pub addr: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub raw_registers: Vec<u16>,
+    pub decoded_value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    pub format: DisplayFormat,
+}
+
+impl Snapshot {
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
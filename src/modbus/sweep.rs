@@ -0,0 +1,46 @@
+// Consecutive populated addresses as (first address, count), from addresses
+// in ascending order.
+pub fn populated_blocks(addresses: impl IntoIterator<Item = u16>) -> Vec<(u16, u16)> {
+    let mut blocks: Vec<(u16, u16)> = Vec::new();
+    for address in addresses {
+        match blocks.last_mut() {
+            Some((first, count)) if *first as u32 + *count as u32 == address as u32 => *count += 1,
+            _ => blocks.push((address, 1)),
+        }
+    }
+    blocks
+}
+
+// the sweep range cut into requests of at most `max` addresses, `to` included
+pub fn chunks(from: u16, to: u16, max: u16) -> Vec<(u16, u16)> {
+    let mut chunks = Vec::new();
+    let mut address = from as u32;
+    while address <= to as u32 {
+        let quantity = (to as u32 - address + 1).min(max as u32);
+        chunks.push((address as u16, quantity as u16));
+        address += quantity;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_runs() {
+        assert_eq!(
+            populated_blocks([0, 1, 2, 10, 12, 13]),
+            [(0, 3), (10, 1), (12, 2)]
+        );
+        assert_eq!(populated_blocks([]), []);
+    }
+
+    #[test]
+    fn chunks_respect_the_limit() {
+        assert_eq!(chunks(0, 249, 125), [(0, 125), (125, 125)]);
+        assert_eq!(chunks(100, 100, 125), [(100, 1)]);
+        // up to the last address without overflowing
+        assert_eq!(chunks(65500, 65535, 125), [(65500, 36)]);
+    }
+}
@@ -0,0 +1,117 @@
+use anyhow::{anyhow, Context, Result};
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio_rustls::TlsConnector;
+
+// Modbus/TCP Security (port 802)
+#[derive(Clone, Default)]
+pub struct TlsSettings {
+    pub ca_path: String,
+    pub cert_path: String,
+    pub key_path: String,
+    // lab use only: accept any server certificate
+    pub insecure: bool,
+}
+
+impl TlsSettings {
+    fn client_config(&self) -> Result<ClientConfig> {
+        let provider = Arc::new(ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()?;
+
+        let builder = if self.insecure {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(SkipVerify(provider)))
+        } else {
+            if self.ca_path.is_empty() {
+                return Err(anyhow!("CA certificate required (or enable skip verify)"));
+            }
+            let mut roots = RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(&self.ca_path)
+                .with_context(|| format!("read CA {}", self.ca_path))?
+            {
+                roots.add(cert?)?;
+            }
+            builder.with_root_certificates(roots)
+        };
+
+        // Modbus Security expects mutual auth, but allow anonymous clients for lab servers
+        let config = if self.cert_path.is_empty() {
+            builder.with_no_client_auth()
+        } else {
+            let certs = CertificateDer::pem_file_iter(&self.cert_path)
+                .with_context(|| format!("read certificate {}", self.cert_path))?
+                .collect::<Result<Vec<_>, _>>()?;
+            let key = PrivateKeyDer::from_pem_file(&self.key_path)
+                .with_context(|| format!("read key {}", self.key_path))?;
+            builder.with_client_auth_cert(certs, key)?
+        };
+
+        Ok(config)
+    }
+
+    pub async fn connect(&self, host: &str, port: u16) -> Result<TlsStream<TcpStream>> {
+        let connector = TlsConnector::from(Arc::new(self.client_config()?));
+        let server_name = ServerName::try_from(host.to_string())?;
+
+        let stream = TcpStream::connect((host, port)).await?;
+        Ok(connector.connect(server_name, stream).await?)
+    }
+}
+
+#[derive(Debug)]
+struct SkipVerify(Arc<tokio_rustls::rustls::crypto::CryptoProvider>);
+
+impl ServerCertVerifier for SkipVerify {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, tokio_rustls::rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, tokio_rustls::rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
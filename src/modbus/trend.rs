@@ -0,0 +1,103 @@
+use super::display::{ByteOrder, DisplayFormat};
+use egui_plot::{Line, Plot, PlotPoints};
+use std::collections::VecDeque;
+use std::time::Instant;
+
+// formats that decode to a number
+pub const TREND_FORMATS: [DisplayFormat; 5] = [
+    DisplayFormat::Signed,
+    DisplayFormat::Unsigned,
+    DisplayFormat::Long,
+    DisplayFormat::Float,
+    DisplayFormat::Double,
+];
+
+// One register followed over time while Auto Poll runs. Points are
+// (seconds since the first sample, value); the oldest drop off past max_points.
+pub struct Trend {
+    pub address: u16,
+    pub format: DisplayFormat,
+    pub max_points: usize,
+    points: VecDeque<[f64; 2]>,
+    started: Option<Instant>,
+}
+
+impl Trend {
+    pub fn new() -> Self {
+        Trend {
+            address: 0,
+            format: DisplayFormat::Signed,
+            max_points: 600,
+            points: VecDeque::new(),
+            started: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.started = None;
+    }
+
+    // `data` is a read that started at `start`; ignored when it doesn't cover the address
+    pub fn push(&mut self, start: u16, data: &[u16], order: ByteOrder) {
+        let Some(offset) = self.address.checked_sub(start) else {
+            return;
+        };
+        let Some(value) = data
+            .get(offset as usize..)
+            .and_then(|regs| self.format.value(regs, order))
+        else {
+            return;
+        };
+
+        let started = *self.started.get_or_insert_with(Instant::now);
+        self.points
+            .push_back([started.elapsed().as_secs_f64(), value]);
+        while self.points.len() > self.max_points {
+            self.points.pop_front();
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        let (address, format) = (self.address, self.format);
+
+        ui.horizontal(|ui| {
+            ui.label("Address");
+            ui.add(egui::DragValue::new(&mut self.address));
+
+            ui.label("As");
+            egui::ComboBox::from_id_salt("trend_format")
+                .selected_text(self.format.label())
+                .show_ui(ui, |ui| {
+                    for format in TREND_FORMATS {
+                        ui.selectable_value(&mut self.format, format, format.label());
+                    }
+                });
+
+            ui.label("History");
+            ui.add(
+                egui::DragValue::new(&mut self.max_points)
+                    .range(10..=100_000)
+                    .suffix(" pts"),
+            );
+
+            if ui.button("Clear").clicked() {
+                self.clear();
+            }
+
+            ui.label(format!("{} points", self.points.len()));
+        });
+
+        if (address, format) != (self.address, self.format) {
+            self.clear();
+        }
+
+        let points: PlotPoints = self.points.iter().copied().collect();
+        Plot::new("modbus_trend")
+            .height(180.0)
+            .x_axis_label("s")
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(self.address.to_string(), points));
+            });
+    }
+}
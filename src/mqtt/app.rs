@@ -0,0 +1,861 @@
+use super::topics::{json_tree, Topics, HISTORY_PER_TOPIC};
+use crate::message::AppMessage;
+use crate::serial::utils::{bytes_to_hex_string, now_timestamp};
+use rumqttc::{AsyncClient, Event, MqttOptions, Outgoing, Packet, QoS};
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::time::{Duration, Instant};
+
+// while connected, so incoming messages show up without moving the mouse
+const REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+
+// reconnect backoff, doubling from the first wait up to the last
+const RECONNECT_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(30);
+// how often a backoff wait looks for Disconnect
+const STOP_CHECK: Duration = Duration::from_millis(100);
+
+// publishes held while the broker is unreachable; the oldest go first
+const OFFLINE_QUEUE_MAX: usize = 100;
+
+// log lines kept, a busy broker would otherwise grow the log without end
+const MAX_LOG_LINES: usize = 10_000;
+
+// what the area under Publish shows
+#[derive(PartialEq)]
+pub enum MqttView {
+    // every event in arrival order
+    Log,
+    // latest value per topic, with each topic's history
+    Topics,
+}
+
+// a publish made while offline, sent once the broker is back
+struct QueuedPublish {
+    topic: String,
+    payload: Vec<u8>,
+    qos: QoS,
+    retain: bool,
+}
+
+const QOS_LEVELS: [QoS; 3] = [QoS::AtMostOnce, QoS::AtLeastOnce, QoS::ExactlyOnce];
+
+fn qos_label(qos: QoS) -> &'static str {
+    match qos {
+        QoS::AtMostOnce => "QoS 0",
+        QoS::AtLeastOnce => "QoS 1",
+        QoS::ExactlyOnce => "QoS 2",
+    }
+}
+
+pub struct MqttTool {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: String,
+    pub password: String,
+    pub keep_alive_secs: u64,
+    // keep polling after a dropped connection, with backoff
+    pub auto_reconnect: bool,
+
+    // subscribed again after every (re)connect
+    pub subscriptions: Vec<(String, QoS)>,
+    pub new_topic: String,
+    pub subscribe_qos: QoS,
+
+    pub publish_topic: String,
+    pub publish_payload: String,
+    pub publish_qos: QoS,
+    pub retain: bool,
+    queued: VecDeque<QueuedPublish>,
+    // handed to the client, waiting for the event loop to give them a packet
+    // id; it sends them in order
+    unsent: VecDeque<(String, QoS)>,
+    // QoS 1/2 publishes by packet id, until the broker acknowledged them
+    in_flight: HashMap<u16, (String, QoS)>,
+
+    // payloads as hex instead of UTF-8 text
+    pub show_hex: bool,
+    pub logs: VecDeque<String>,
+    pub scroll_to_bottom: bool,
+    pub view: MqttView,
+    topics: Topics,
+    // whose history shows under the topic table
+    selected_topic: Option<String>,
+
+    // set between Connect and Disconnect
+    client: Option<AsyncClient>,
+    pub connected: bool,
+    // stops the event loop while it waits to reconnect
+    stop_tx: Option<Sender<()>>,
+    // when the event loop tries the broker again
+    retry_at: Option<Instant>,
+
+    tx: Sender<AppMessage>,
+    rx: Receiver<AppMessage>,
+    // None when the runtime failed to start; the tab is then disabled
+    rt: Option<tokio::runtime::Runtime>,
+
+    pub status: String,
+}
+
+impl MqttTool {
+    pub fn new() -> Self {
+        let (rt, status) = match tokio::runtime::Runtime::new() {
+            Ok(rt) => (Some(rt), "Disconnected".to_string()),
+            Err(e) => (None, format!("Async runtime unavailable: {}", e)),
+        };
+
+        let (tx, rx) = channel::<AppMessage>();
+
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 1883,
+            client_id: format!("iot-toolbox-{}", std::process::id()),
+            username: String::new(),
+            password: String::new(),
+            keep_alive_secs: 30,
+            auto_reconnect: true,
+
+            subscriptions: Vec::new(),
+            new_topic: String::new(),
+            subscribe_qos: QoS::AtMostOnce,
+
+            publish_topic: String::new(),
+            publish_payload: String::new(),
+            publish_qos: QoS::AtMostOnce,
+            retain: false,
+            queued: VecDeque::new(),
+            unsent: VecDeque::new(),
+            in_flight: HashMap::new(),
+
+            show_hex: false,
+            logs: VecDeque::new(),
+            scroll_to_bottom: false,
+            view: MqttView::Log,
+            topics: Topics::new(),
+            selected_topic: None,
+
+            client: None,
+            connected: false,
+            stop_tx: None,
+            retry_at: None,
+            tx,
+            rx,
+            rt,
+
+            status,
+        }
+    }
+
+    // Runs every frame whichever tab is shown, so a reconnect resubscribes and
+    // flushes the queue while the user is elsewhere.
+    pub fn tick(&mut self, ctx: &egui::Context) {
+        let messages: Vec<AppMessage> = self.rx.try_iter().collect();
+        for msg in messages {
+            self.handle_message(msg);
+        }
+        if self.client.is_some() {
+            ctx.request_repaint_after(REPAINT_INTERVAL);
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        if self.rt.is_none() {
+            ui.vertical_centered(|ui| {
+                ui.add_space(40.0);
+                ui.label(
+                    egui::RichText::new("MQTT is disabled")
+                        .strong()
+                        .color(egui::Color32::RED),
+                );
+                ui.label(&self.status);
+            });
+            return;
+        }
+
+        ui.vertical(|ui| {
+            self.ui_connection(ui);
+            self.ui_subscriptions(ui);
+            self.ui_publish(ui);
+            self.ui_logs(ui);
+            self.ui_status(ui);
+        });
+    }
+
+    fn handle_message(&mut self, msg: AppMessage) {
+        match msg {
+            // a session that was replaced or closed by Disconnect
+            AppMessage::MqttConnected | AppMessage::MqttLost { .. } if self.client.is_none() => {}
+            AppMessage::MqttConnected => {
+                let again = if self.retry_at.take().is_some() {
+                    "Reconnected"
+                } else {
+                    "Connected"
+                };
+                self.connected = true;
+                self.status = format!("{} to {}:{}", again, self.host, self.port);
+                self.log(format!("{} to {}:{}", again, self.host, self.port));
+
+                // the broker forgets them with a clean session
+                for (topic, qos) in self.subscriptions.clone() {
+                    self.subscribe(&topic, qos);
+                }
+                self.flush_queue();
+            }
+            AppMessage::MqttLost { error, retry_in } => {
+                self.connected = false;
+                self.forget_in_flight();
+                match retry_in {
+                    Some(delay) => {
+                        self.retry_at = Some(Instant::now() + delay);
+                        self.status = format!(
+                            "Connection lost: {}, retrying in {} s",
+                            error,
+                            delay.as_secs()
+                        );
+                        self.log(format!("ERR {}, retrying in {} s", error, delay.as_secs()));
+                    }
+                    None => {
+                        self.client = None;
+                        self.stop_tx = None;
+                        self.retry_at = None;
+                        self.status = format!("Connection lost: {}", error);
+                        self.log(format!("ERR {}", error));
+                        self.drop_queue();
+                    }
+                }
+            }
+            AppMessage::MqttMessage {
+                topic,
+                payload,
+                qos,
+                retain,
+            } => {
+                let text = self.payload_text(&payload);
+                let retained = if retain { ", retained" } else { "" };
+                self.log(format!(
+                    "RX {} ({}{}): {}",
+                    topic,
+                    qos_label(qos),
+                    retained,
+                    text
+                ));
+                self.topics.record(&topic, now_timestamp(), payload, retain);
+            }
+            AppMessage::MqttPublished(pkid) => {
+                let Some((topic, qos)) = self.unsent.pop_front() else {
+                    return;
+                };
+                if qos == QoS::AtMostOnce {
+                    self.status = format!("Published to {} (QoS 0, no ack)", topic);
+                } else {
+                    let ack = if qos == QoS::AtLeastOnce {
+                        "PUBACK"
+                    } else {
+                        "PUBREC"
+                    };
+                    self.status = format!("Published to {}, waiting for {}", topic, ack);
+                    self.in_flight.insert(pkid, (topic, qos));
+                }
+            }
+            AppMessage::MqttPubAck(pkid) => {
+                if let Some((topic, _)) = self.in_flight.remove(&pkid) {
+                    self.status = format!("Publish to {} acknowledged (QoS 1)", topic);
+                    self.log(format!("PUBACK {} (id {})", topic, pkid));
+                }
+            }
+            AppMessage::MqttPubRec(pkid) => {
+                if let Some((topic, _)) = self.in_flight.get(&pkid) {
+                    self.status = format!("Publish to {} received, waiting for PUBCOMP", topic);
+                    self.log(format!("PUBREC {} (id {})", topic, pkid));
+                }
+            }
+            AppMessage::MqttPubComp(pkid) => {
+                if let Some((topic, _)) = self.in_flight.remove(&pkid) {
+                    self.status = format!("Publish to {} completed (QoS 2)", topic);
+                    self.log(format!("PUBCOMP {} (id {})", topic, pkid));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn payload_text(&self, payload: &[u8]) -> String {
+        if self.show_hex {
+            bytes_to_hex_string(payload)
+        } else {
+            String::from_utf8_lossy(payload).into_owned()
+        }
+    }
+
+    fn log(&mut self, line: String) {
+        self.logs
+            .push_back(format!("[{}] {}", now_timestamp(), line));
+        if self.logs.len() > MAX_LOG_LINES {
+            self.logs.pop_front();
+        }
+        self.scroll_to_bottom = true;
+    }
+
+    fn ui_connection(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(egui::RichText::new("Broker").strong());
+
+            // the session was opened with these settings, disconnect to change them
+            ui.add_enabled_ui(self.client.is_none(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Host");
+                    ui.text_edit_singleline(&mut self.host);
+
+                    ui.label("Port");
+                    ui.add(egui::DragValue::new(&mut self.port));
+
+                    ui.label("Client ID");
+                    ui.text_edit_singleline(&mut self.client_id);
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Username");
+                    ui.add(egui::TextEdit::singleline(&mut self.username).desired_width(120.0));
+
+                    ui.label("Password");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.password)
+                            .password(true)
+                            .desired_width(120.0),
+                    );
+
+                    ui.label("Keep alive");
+                    ui.add(
+                        egui::DragValue::new(&mut self.keep_alive_secs)
+                            .range(5..=3600)
+                            .suffix(" s"),
+                    );
+
+                    ui.checkbox(&mut self.auto_reconnect, "Auto-reconnect")
+                        .on_hover_text("Reconnect and resubscribe when the connection drops");
+                });
+            });
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        self.client.is_none(),
+                        egui::Button::new(
+                            egui::RichText::new("Connect").color(egui::Color32::BLUE),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.connect();
+                }
+                if ui
+                    .add_enabled(
+                        self.client.is_some(),
+                        egui::Button::new(
+                            egui::RichText::new("Disconnect").color(egui::Color32::RED),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.disconnect();
+                }
+
+                if self.connected {
+                    ui.label(egui::RichText::new("● Connected").color(egui::Color32::DARK_GREEN));
+                } else if let Some(at) = self.retry_at {
+                    let wait = at.saturating_duration_since(Instant::now());
+                    if wait.is_zero() {
+                        ui.label(
+                            egui::RichText::new("● Reconnecting...").color(egui::Color32::ORANGE),
+                        );
+                    } else {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "● Offline, retrying in {} s",
+                                wait.as_secs() + 1
+                            ))
+                            .color(egui::Color32::ORANGE),
+                        );
+                    }
+                } else if self.client.is_some() {
+                    ui.label("Connecting...");
+                }
+            });
+        });
+    }
+
+    fn ui_subscriptions(&mut self, ui: &mut egui::Ui) {
+        let mut remove = None;
+
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(egui::RichText::new("Subscriptions").strong());
+
+            ui.horizontal_wrapped(|ui| {
+                for (i, (topic, qos)) in self.subscriptions.iter().enumerate() {
+                    let button = ui
+                        .button(topic)
+                        .on_hover_text(format!("{}, click to unsubscribe", qos_label(*qos)));
+                    if button.clicked() {
+                        remove = Some(i);
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.new_topic)
+                        .hint_text("sensors/+/temperature")
+                        .desired_width(240.0),
+                );
+                qos_combo(ui, "subscribe_qos", &mut self.subscribe_qos);
+
+                let topic = self.new_topic.trim().to_string();
+                let known = self.subscriptions.iter().any(|(t, _)| *t == topic);
+                if ui
+                    .add_enabled(!topic.is_empty() && !known, egui::Button::new("Subscribe"))
+                    .clicked()
+                {
+                    let qos = self.subscribe_qos;
+                    self.subscriptions.push((topic.clone(), qos));
+                    self.new_topic.clear();
+                    if self.connected {
+                        self.subscribe(&topic, qos);
+                    }
+                }
+            });
+        });
+
+        if let Some(i) = remove {
+            let (topic, _) = self.subscriptions.remove(i);
+            if let Some(client) = &self.client {
+                match client.try_unsubscribe(topic.as_str()) {
+                    Ok(()) => self.log(format!("Unsubscribed {}", topic)),
+                    Err(e) => self.log(format!("ERR unsubscribe {}: {}", topic, e)),
+                }
+            }
+        }
+    }
+
+    fn ui_publish(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(egui::RichText::new("Publish").strong());
+
+            ui.horizontal(|ui| {
+                ui.label("Topic");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.publish_topic)
+                        .hint_text("devices/42/cmd")
+                        .desired_width(240.0),
+                );
+                qos_combo(ui, "publish_qos", &mut self.publish_qos);
+                ui.checkbox(&mut self.retain, "Retain");
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.publish_payload)
+                        .hint_text("payload")
+                        .desired_rows(2)
+                        .desired_width(400.0),
+                );
+                if ui
+                    .add_enabled(
+                        self.client.is_some() && !self.publish_topic.trim().is_empty(),
+                        egui::Button::new(
+                            egui::RichText::new("Publish").color(egui::Color32::BLUE),
+                        ),
+                    )
+                    .on_disabled_hover_text("Connect and enter a topic first")
+                    .clicked()
+                {
+                    self.publish();
+                }
+                if !self.queued.is_empty() {
+                    ui.label(format!("{} queued until reconnected", self.queued.len()));
+                }
+            });
+        });
+    }
+
+    fn ui_logs(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.view, MqttView::Log, "Log");
+            ui.radio_value(&mut self.view, MqttView::Topics, "Topics")
+                .on_hover_text("Latest value per topic, with its history");
+            ui.separator();
+            ui.checkbox(&mut self.show_hex, "Hex payloads");
+            if ui.button("Clear").clicked() {
+                match self.view {
+                    MqttView::Log => self.logs.clear(),
+                    MqttView::Topics => self.topics.clear(),
+                }
+            }
+        });
+
+        if self.view == MqttView::Topics {
+            self.ui_topics(ui);
+            return;
+        }
+
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .auto_shrink([false; 2])
+                .stick_to_bottom(self.scroll_to_bottom)
+                .show(ui, |ui| {
+                    for log in &self.logs {
+                        ui.monospace(log);
+                    }
+                });
+
+            self.scroll_to_bottom = false;
+        });
+    }
+
+    fn ui_topics(&mut self, ui: &mut egui::Ui) {
+        let mut pin = None;
+        let mut select = None;
+
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            if self.topics.topics.is_empty() {
+                ui.weak("Nothing received yet");
+                return;
+            }
+
+            egui::ScrollArea::vertical()
+                .id_salt("mqtt_topics")
+                .max_height(200.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    egui::Grid::new("mqtt_topic_table")
+                        .striped(true)
+                        .num_columns(6)
+                        .show(ui, |ui| {
+                            ui.label("");
+                            ui.strong("Topic");
+                            ui.strong("Msgs");
+                            ui.strong("Latest");
+                            ui.strong("Retained");
+                            ui.strong("Time");
+                            ui.end_row();
+
+                            for (name, topic) in self.topics.ordered() {
+                                let mut pinned = topic.pinned;
+                                if ui
+                                    .toggle_value(&mut pinned, "Pin")
+                                    .on_hover_text("Keep at the top, and across Clear")
+                                    .clicked()
+                                {
+                                    pin = Some((name.clone(), pinned));
+                                }
+
+                                let selected = self.selected_topic.as_ref() == Some(name);
+                                if ui.selectable_label(selected, name).clicked() {
+                                    select = Some(name.clone());
+                                }
+                                ui.label(topic.received.to_string());
+
+                                let latest = topic.latest();
+                                ui.monospace(short(
+                                    &latest
+                                        .map(|m| self.payload_text(&m.payload))
+                                        .unwrap_or_default(),
+                                ));
+                                match &topic.retained {
+                                    Some(m) => ui.monospace(short(&self.payload_text(&m.payload))),
+                                    None => ui.weak("-"),
+                                };
+                                ui.label(latest.map(|m| m.at.as_str()).unwrap_or(""));
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+
+        if let Some((name, pinned)) = pin {
+            if let Some(topic) = self.topics.topics.get_mut(&name) {
+                topic.pinned = pinned;
+            }
+        }
+        if let Some(name) = select {
+            self.selected_topic = Some(name);
+        }
+
+        let Some(name) = &self.selected_topic else {
+            return;
+        };
+        let Some(topic) = self.topics.topics.get(name) else {
+            return;
+        };
+
+        ui.label(
+            egui::RichText::new(format!(
+                "{} - last {} of {} (up to {} kept)",
+                name,
+                topic.history.len(),
+                topic.received,
+                HISTORY_PER_TOPIC
+            ))
+            .strong(),
+        );
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .id_salt("mqtt_topic_history")
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    // newest on top, like the table's Latest column
+                    for message in topic.history.iter().rev() {
+                        ui.horizontal(|ui| {
+                            ui.monospace(&message.at);
+                            if message.retain {
+                                ui.label(
+                                    egui::RichText::new("retained").color(egui::Color32::ORANGE),
+                                );
+                            }
+                        });
+                        match message.json() {
+                            Some(value) if !self.show_hex => {
+                                let id = egui::Id::new(("mqtt_json", name, message.seq));
+                                json_tree(ui, id, "payload", &value);
+                            }
+                            _ => {
+                                ui.monospace(self.payload_text(&message.payload));
+                            }
+                        }
+                        ui.separator();
+                    }
+                });
+        });
+    }
+
+    fn ui_status(&mut self, ui: &mut egui::Ui) {
+        egui::TopBottomPanel::bottom("mqtt_status").show(ui.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Status: ").color(egui::Color32::RED));
+                ui.monospace(&self.status);
+            });
+        });
+    }
+
+    fn connect(&mut self) {
+        if self.client.is_some() {
+            return;
+        }
+        let Some(rt) = &self.rt else {
+            return;
+        };
+
+        let mut options = MqttOptions::new(self.client_id.trim(), self.host.trim(), self.port);
+        options.set_keep_alive(Duration::from_secs(self.keep_alive_secs));
+        if !self.username.is_empty() {
+            options.set_credentials(self.username.as_str(), self.password.as_str());
+        }
+
+        let (client, mut eventloop) = AsyncClient::new(options, 64);
+        let (stop_tx, stop_rx) = channel::<()>();
+        self.client = Some(client);
+        self.stop_tx = Some(stop_tx);
+        self.retry_at = None;
+        self.status = "Connecting...".into();
+
+        let tx = self.tx.clone();
+        let auto_reconnect = self.auto_reconnect;
+        rt.spawn(async move {
+            let mut backoff = RECONNECT_MIN;
+            loop {
+                let message = match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        backoff = RECONNECT_MIN;
+                        AppMessage::MqttConnected
+                    }
+                    Ok(Event::Incoming(Packet::Publish(publish))) => AppMessage::MqttMessage {
+                        topic: publish.topic,
+                        payload: publish.payload.to_vec(),
+                        qos: publish.qos,
+                        retain: publish.retain,
+                    },
+                    Ok(Event::Outgoing(Outgoing::Publish(pkid))) => AppMessage::MqttPublished(pkid),
+                    Ok(Event::Incoming(Packet::PubAck(ack))) => AppMessage::MqttPubAck(ack.pkid),
+                    Ok(Event::Incoming(Packet::PubRec(rec))) => AppMessage::MqttPubRec(rec.pkid),
+                    Ok(Event::Incoming(Packet::PubComp(comp))) => {
+                        AppMessage::MqttPubComp(comp.pkid)
+                    }
+                    // Disconnect went out, the session is over
+                    Ok(Event::Outgoing(Outgoing::Disconnect)) => return,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        let retry_in = auto_reconnect.then_some(backoff);
+                        let lost = AppMessage::MqttLost {
+                            error: e.to_string(),
+                            retry_in,
+                        };
+                        if tx.send(lost).is_err() {
+                            return;
+                        }
+                        let Some(delay) = retry_in else {
+                            return;
+                        };
+
+                        // the next poll reconnects; wait first so a dead
+                        // broker isn't hammered, unless Disconnect comes
+                        let until = Instant::now() + delay;
+                        loop {
+                            if !matches!(stop_rx.try_recv(), Err(TryRecvError::Empty)) {
+                                return;
+                            }
+                            let left = until.saturating_duration_since(Instant::now());
+                            if left.is_zero() {
+                                break;
+                            }
+                            tokio::time::sleep(left.min(STOP_CHECK)).await;
+                        }
+                        backoff = (backoff * 2).min(RECONNECT_MAX);
+                        continue;
+                    }
+                };
+                if tx.send(message).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    pub fn disconnect(&mut self) {
+        let Some(client) = self.client.take() else {
+            return;
+        };
+        let _ = client.try_disconnect();
+        if let Some(stop) = self.stop_tx.take() {
+            let _ = stop.send(());
+        }
+
+        self.connected = false;
+        self.retry_at = None;
+        self.status = "Disconnected".into();
+        self.log("Disconnected".into());
+        self.forget_in_flight();
+        self.drop_queue();
+    }
+
+    fn subscribe(&mut self, topic: &str, qos: QoS) {
+        let Some(client) = &self.client else {
+            return;
+        };
+        match client.try_subscribe(topic, qos) {
+            Ok(()) => self.log(format!("Subscribed {} ({})", topic, qos_label(qos))),
+            Err(e) => self.log(format!("ERR subscribe {}: {}", topic, e)),
+        }
+    }
+
+    fn publish(&mut self) {
+        if self.client.is_none() {
+            return;
+        }
+        let publish = QueuedPublish {
+            topic: self.publish_topic.trim().to_string(),
+            payload: self.publish_payload.clone().into_bytes(),
+            qos: self.publish_qos,
+            retain: self.retain,
+        };
+
+        if self.connected {
+            self.send_publish(publish);
+            return;
+        }
+
+        // rumqttc drops requests it couldn't send when a clean session
+        // starts, so hold them here and send after the ConnAck
+        if self.queued.len() == OFFLINE_QUEUE_MAX {
+            if let Some(oldest) = self.queued.pop_front() {
+                self.log(format!(
+                    "ERR queue full ({}), dropped publish to {}",
+                    OFFLINE_QUEUE_MAX, oldest.topic
+                ));
+            }
+        }
+        self.log(format!(
+            "Queued {}: {} (sent when reconnected)",
+            publish.topic, self.publish_payload
+        ));
+        self.queued.push_back(publish);
+    }
+
+    fn send_publish(&mut self, publish: QueuedPublish) {
+        let Some(client) = &self.client else {
+            return;
+        };
+        let text = String::from_utf8_lossy(&publish.payload).into_owned();
+
+        match client.try_publish(
+            publish.topic.as_str(),
+            publish.qos,
+            publish.retain,
+            publish.payload,
+        ) {
+            Ok(()) => {
+                self.log(format!("TX {}: {}", publish.topic, text));
+                self.unsent.push_back((publish.topic, publish.qos));
+            }
+            Err(e) => {
+                self.status = format!("Publish failed: {}", e);
+                self.log(format!("ERR publish {}: {}", publish.topic, e));
+            }
+        }
+    }
+
+    fn flush_queue(&mut self) {
+        if self.queued.is_empty() {
+            return;
+        }
+        self.log(format!("Sending {} queued publishes", self.queued.len()));
+        while let Some(publish) = self.queued.pop_front() {
+            self.send_publish(publish);
+        }
+    }
+
+    // a new session starts clean, acks for these will never come
+    fn forget_in_flight(&mut self) {
+        if !self.in_flight.is_empty() {
+            self.log(format!(
+                "ERR {} publishes never acknowledged",
+                self.in_flight.len()
+            ));
+        }
+        self.in_flight.clear();
+        self.unsent.clear();
+    }
+
+    // the session they were meant for is over
+    fn drop_queue(&mut self) {
+        if !self.queued.is_empty() {
+            self.log(format!("Dropped {} queued publishes", self.queued.len()));
+            self.queued.clear();
+        }
+    }
+}
+
+// first line of a payload, cut to fit a table cell
+fn short(text: &str) -> String {
+    const MAX: usize = 60;
+    let line = text.lines().next().unwrap_or("");
+    if line.chars().count() > MAX || line.len() < text.trim_end().len() {
+        format!("{}…", line.chars().take(MAX).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+fn qos_combo(ui: &mut egui::Ui, id: &str, qos: &mut QoS) {
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(qos_label(*qos))
+        .show_ui(ui, |ui| {
+            for level in QOS_LEVELS {
+                ui.selectable_value(qos, level, qos_label(level));
+            }
+        });
+}
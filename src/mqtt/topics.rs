@@ -0,0 +1,177 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+
+// messages kept per topic before the oldest drop off
+pub const HISTORY_PER_TOPIC: usize = 200;
+
+#[derive(Clone)]
+pub struct TopicMessage {
+    // counts up per topic, so the JSON tree keeps its open state as
+    // older messages drop off
+    pub seq: u64,
+    pub at: String,
+    pub payload: Vec<u8>,
+    pub retain: bool,
+}
+
+impl TopicMessage {
+    // objects and arrays only; a bare number or string reads fine as text
+    pub fn json(&self) -> Option<Value> {
+        match serde_json::from_slice(&self.payload) {
+            Ok(value @ (Value::Object(_) | Value::Array(_))) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Topic {
+    pub history: VecDeque<TopicMessage>,
+    // everything received, history only keeps the newest
+    pub received: u64,
+    // the last retained message, what the broker hands a new subscriber
+    pub retained: Option<TopicMessage>,
+    pub pinned: bool,
+}
+
+impl Topic {
+    pub fn latest(&self) -> Option<&TopicMessage> {
+        self.history.back()
+    }
+}
+
+// Received messages grouped by the concrete topic they arrived on.
+pub struct Topics {
+    pub topics: BTreeMap<String, Topic>,
+}
+
+impl Topics {
+    pub fn new() -> Self {
+        Topics {
+            topics: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, topic: &str, at: String, payload: Vec<u8>, retain: bool) {
+        let entry = self.topics.entry(topic.to_string()).or_default();
+        let message = TopicMessage {
+            seq: entry.received,
+            at,
+            payload,
+            retain,
+        };
+        entry.received += 1;
+
+        if retain {
+            entry.retained = Some(message.clone());
+        }
+        entry.history.push_back(message);
+        while entry.history.len() > HISTORY_PER_TOPIC {
+            entry.history.pop_front();
+        }
+    }
+
+    // pinned topics stay listed, with their history emptied
+    pub fn clear(&mut self) {
+        self.topics.retain(|_, topic| topic.pinned);
+        for topic in self.topics.values_mut() {
+            *topic = Topic {
+                pinned: true,
+                ..Topic::default()
+            };
+        }
+    }
+
+    // pinned first, each group by name
+    pub fn ordered(&self) -> Vec<(&String, &Topic)> {
+        let mut topics: Vec<_> = self.topics.iter().collect();
+        topics.sort_by_key(|(_, topic)| !topic.pinned);
+        topics
+    }
+}
+
+// Expandable view of a JSON payload, objects and arrays as collapsing
+// headers with their leaves as `key: value` rows.
+pub fn json_tree(ui: &mut egui::Ui, id: egui::Id, key: &str, value: &Value) {
+    match value {
+        Value::Object(map) => {
+            egui::CollapsingHeader::new(format!("{} {{{}}}", key, map.len()))
+                .id_salt(id)
+                .show(ui, |ui| {
+                    for (k, v) in map {
+                        json_tree(ui, id.with(k), k, v);
+                    }
+                });
+        }
+        Value::Array(items) => {
+            egui::CollapsingHeader::new(format!("{} [{}]", key, items.len()))
+                .id_salt(id)
+                .show(ui, |ui| {
+                    for (i, v) in items.iter().enumerate() {
+                        json_tree(ui, id.with(i), &i.to_string(), v);
+                    }
+                });
+        }
+        leaf => {
+            ui.horizontal(|ui| {
+                ui.label(format!("{}:", key));
+                ui.monospace(leaf.to_string());
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history_is_bounded_and_keeps_retained() {
+        let mut topics = Topics::new();
+        topics.record("a/b", "t0".into(), b"first".to_vec(), true);
+        for i in 0..HISTORY_PER_TOPIC {
+            topics.record(
+                "a/b",
+                format!("t{}", i + 1),
+                i.to_string().into_bytes(),
+                false,
+            );
+        }
+
+        let topic = &topics.topics["a/b"];
+        assert_eq!(topic.history.len(), HISTORY_PER_TOPIC);
+        assert_eq!(topic.received, HISTORY_PER_TOPIC as u64 + 1);
+        assert_eq!(topic.history.front().unwrap().seq, 1);
+        assert_eq!(topic.retained.as_ref().unwrap().payload, b"first");
+    }
+
+    #[test]
+    fn pinned_topics_come_first_and_survive_clear() {
+        let mut topics = Topics::new();
+        for name in ["a", "b", "c"] {
+            topics.record(name, String::new(), Vec::new(), false);
+        }
+        topics.topics.get_mut("c").unwrap().pinned = true;
+
+        let names: Vec<&str> = topics.ordered().iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["c", "a", "b"]);
+
+        topics.clear();
+        assert_eq!(topics.topics.len(), 1);
+        assert!(topics.topics["c"].history.is_empty());
+    }
+
+    #[test]
+    fn only_structured_payloads_are_json() {
+        let message = |payload: &str| TopicMessage {
+            seq: 0,
+            at: String::new(),
+            payload: payload.as_bytes().to_vec(),
+            retain: false,
+        };
+        assert!(message(r#"{"temp": 21.5}"#).json().is_some());
+        assert!(message("[1, 2]").json().is_some());
+        assert!(message("21.5").json().is_none());
+        assert!(message("not json").json().is_none());
+    }
+}
@@ -1,81 +1,314 @@
-use super::utils::{bytes_to_hex_string, now_timestamp, parse_hex_string};
+use super::at::{AtRequest, COMMON_COMMANDS};
+use super::baud::{self, BaudCandidate};
+use super::diff::LogDiff;
+use super::history::SendHistory;
+use super::log::{Direction, LogEntry, LogFile};
+use super::loopback::Loopback;
+use super::macros::{self, MacroDraft, SerialMacro, MACROS_FILE};
+use super::nmea::{self, Checksum, Sentence};
+use super::plot::SerialPlot;
+use super::settings::{serial_settings_ui, SerialPortSettings};
+use super::transfer::Transfer;
+use super::utils::{
+    bytes_to_hex_string, hexdump, hexdump_row, now_timestamp, parse_hex_string, HexStyle,
+};
+use crate::message::AppMessage;
 use eframe::egui;
-use serialport::{self, SerialPort, SerialPortInfo};
+use serde::{Deserialize, Serialize};
+use serialport::{self, SerialPort};
+use std::collections::{BTreeMap, VecDeque};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Receiver;
 use std::sync::mpsc::{self, Sender};
 use std::sync::{Arc, Mutex};
-use std::thread;
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SendFormat {
     Hex,
     Ascii,
 }
 
+// how received bytes are shown, independent of the send format
+#[derive(Clone, Copy, PartialEq)]
+pub enum RxDisplay {
+    Hex,
+    Ascii,
+    Both,
+    // offset / hex / ASCII rows over the whole RX stream instead of per entry
+    Dump,
+}
+
+// where RX is cut into log entries; None logs each received frame as is
+#[derive(Clone, Copy, PartialEq)]
+pub enum RxDelimiter {
+    None,
+    Newline,
+    Custom,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ControlLine {
+    Dtr,
+    Rts,
+}
+
+// terminator appended to ASCII sends
+#[derive(Clone, Copy, PartialEq)]
+pub enum LineEnding {
+    None,
+    Cr,
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LineEnding::None => "None",
+            LineEnding::Cr => "CR",
+            LineEnding::Lf => "LF",
+            LineEnding::CrLf => "CRLF",
+        }
+    }
+
+    pub fn bytes(&self) -> &'static [u8] {
+        match self {
+            LineEnding::None => b"",
+            LineEnding::Cr => b"\r",
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LogLayout {
+    Combined,
+    // TX and RX in their own columns, one row per line so timing stays aligned
+    Split,
+}
+
+// RX bytes kept for the Dump view
+const RX_STREAM_MAX: usize = 64 * 1024;
+
+// RX held back while paused; past this the oldest bytes are dropped
+const PAUSE_BUFFER_MAX: usize = 4 * 1024 * 1024;
+
+// one read held back while paused, with when it arrived
+struct HeldRx {
+    at: Instant,
+    ts: String,
+    data: Vec<u8>,
+}
+
+// time per frame spent pushing a send out, the UI stays responsive meanwhile
+const TX_FRAME_BUDGET: Duration = Duration::from_millis(15);
+
+// at most one full-buffer note per this long, a saturated link fills every read
+const BUFFER_FULL_REPORT: Duration = Duration::from_secs(5);
+
+// how often the port list is polled while waiting to reconnect
+const RECONNECT_CHECK: Duration = Duration::from_secs(1);
+
 pub struct SerialTool {
     // Serial port settings
-    pub available_ports: Vec<SerialPortInfo>,
-    pub selected_port: Option<String>,
-    pub baud_rate: u32,
-    pub data_bits: serialport::DataBits,
-    pub parity: serialport::Parity,
-    pub stop_bits: serialport::StopBits,
-    pub flow_control: serialport::FlowControl,
+    pub port_settings: SerialPortSettings,
+    // last level written to the modem control lines
+    pub dtr: bool,
+    pub rts: bool,
+    // reopen a port that disappeared once it is listed again
+    pub auto_reconnect: bool,
+    reconnect_port: Option<String>,
+    last_port_check: Instant,
+    // bytes taken per read by the reader thread, applied on connect
+    pub read_buffer_size: usize,
     // Logs
-    pub logs: Vec<String>,
+    // oldest entries are dropped past max_log_lines; the log file keeps them
+    pub logs: VecDeque<LogEntry>,
+    pub max_log_lines: usize,
+    pub show_timestamps: bool,
+    // narrows what the log panel shows, never removes entries from self.logs
+    pub log_filter: String,
+    pub log_filter_direction: Option<Direction>,
+    // bytes moved since the log was last cleared
+    pub tx_bytes: u64,
+    pub rx_bytes: u64,
+    // optional on-disk copy of the log
+    pub log_path: String,
+    pub log_file: Option<LogFile>,
     // Input field
     pub input_text: String,
+    // Up/Down in the input recalls earlier sends
+    send_history: SendHistory,
+    // plain Enter sends (Shift+Enter adds a line); Ctrl+Enter always sends
+    pub enter_sends: bool,
     // Connection status
     pub status: String,
     // Serial port connection
     pub port: Option<Arc<Mutex<Box<dyn SerialPort>>>>,
     pub send_format: SendFormat,
+    pub line_ending: LineEnding,
+    // send still going out, written a chunk at a time each frame
+    tx_transfer: Option<Transfer>,
+    // periodic re-send of input_text
+    pub repeat_ms: u64,
+    pub repeating: bool,
+    last_repeat: Instant,
+    // one-click payloads, saved to MACROS_FILE on every change
+    pub macros: Vec<SerialMacro>,
+    macro_draft: Option<MacroDraft>,
+    // why MACROS_FILE couldn't be loaded; nothing is saved over it until
+    // the user reloads or overwrites it
+    pub macros_error: Option<String>,
+    pub rx_display: RxDisplay,
+    pub rx_hex_style: HexStyle,
+    // RX line accumulation
+    pub rx_delimiter: RxDelimiter,
+    pub rx_custom_delimiter: u8,
+    // flush a partial line after this long without data
+    pub rx_idle_ms: u64,
+    rx_buffer: Vec<u8>,
+    rx_last: Instant,
+    // everything received, for the Dump view; rx_stream_offset is the stream
+    // position of its first byte once old rows have been dropped
+    rx_stream: Vec<u8>,
+    rx_stream_offset: usize,
+    // freeze the log without disconnecting; RX is held and replayed on resume
+    pub paused: bool,
+    paused_rx: VecDeque<HeldRx>,
+    // bytes in paused_rx
+    paused_bytes: usize,
+    paused_dropped: usize,
+    // decode RX lines as NMEA 0183, newest sentence per address, e.g. "GPGGA"
+    pub nmea: bool,
+    nmea_sentences: BTreeMap<String, Sentence>,
+    nmea_bad_checksums: u64,
+    pub log_layout: LogLayout,
+    // Log comparison window
+    pub log_diff: LogDiff,
+    // numbers from RX lines plotted over time, fed while its window is open
+    pub plot: SerialPlot,
+    // Baud rate auto-detect: rate being probed, ranked results
+    pub baud_scan: Option<u32>,
+    pub baud_candidates: Vec<BaudCandidate>,
+    // running loopback self-test, RX goes to it instead of the log
+    loopback: Option<Loopback>,
+    // AT command helper: command line, reply deadline, the command waiting
+    // for OK / ERROR and the outcome of the last one
+    pub at_command: String,
+    pub at_timeout_ms: u64,
+    at_request: Option<AtRequest>,
+    at_result: Option<Result<String, String>>,
+    // Width of the settings side panel, remembered between runs
+    pub settings_width: f32,
     // Receiver
-    rx: Option<Receiver<Vec<u8>>>,
+    rx: Option<Receiver<AppMessage>>,
     read_running: Arc<AtomicBool>,
+    read_thread: Option<JoinHandle<()>>,
 }
 
 impl SerialTool {
     pub fn new() -> Self {
-        let available_ports = serialport::available_ports().unwrap_or_default();
+        let (macros, macros_error) = match macros::load(MACROS_FILE) {
+            Ok(macros) => (macros, None),
+            Err(e) => (
+                Vec::new(),
+                Some(format!("Load {} failed: {}", MACROS_FILE, e)),
+            ),
+        };
+
         SerialTool {
-            available_ports,
-            selected_port: None,
-            baud_rate: 9600,
-            data_bits: serialport::DataBits::Eight,
-            parity: serialport::Parity::None,
-            stop_bits: serialport::StopBits::One,
-            flow_control: serialport::FlowControl::None,
-            logs: vec![],
+            port_settings: SerialPortSettings::new(),
+            dtr: true,
+            rts: true,
+            auto_reconnect: false,
+            reconnect_port: None,
+            last_port_check: Instant::now(),
+            read_buffer_size: 256,
+            logs: VecDeque::new(),
+            max_log_lines: 10_000,
+            show_timestamps: true,
+            log_filter: String::new(),
+            log_filter_direction: None,
+            tx_bytes: 0,
+            rx_bytes: 0,
+            log_path: "serial.log".to_string(),
+            log_file: None,
             input_text: String::new(),
-            status: "Disconnected".to_string(),
+            send_history: SendHistory::new(),
+            enter_sends: false,
+            status: macros_error
+                .clone()
+                .unwrap_or_else(|| "Disconnected".to_string()),
             port: None,
             send_format: SendFormat::Hex,
+            line_ending: LineEnding::None,
+            tx_transfer: None,
+            repeat_ms: 1000,
+            repeating: false,
+            last_repeat: Instant::now(),
+            macros,
+            macro_draft: None,
+            macros_error,
+            rx_display: RxDisplay::Hex,
+            rx_hex_style: HexStyle::Spaced,
+            rx_delimiter: RxDelimiter::None,
+            rx_custom_delimiter: b';',
+            rx_idle_ms: 500,
+            rx_buffer: Vec::new(),
+            rx_last: Instant::now(),
+            rx_stream: Vec::new(),
+            rx_stream_offset: 0,
+            paused: false,
+            paused_rx: VecDeque::new(),
+            paused_bytes: 0,
+            paused_dropped: 0,
+            nmea: false,
+            nmea_sentences: BTreeMap::new(),
+            nmea_bad_checksums: 0,
+            log_layout: LogLayout::Combined,
+            log_diff: LogDiff::new(),
+            plot: SerialPlot::new(),
+            baud_scan: None,
+            baud_candidates: Vec::new(),
+            loopback: None,
+            at_command: "AT".to_string(),
+            at_timeout_ms: 1000,
+            at_request: None,
+            at_result: None,
+            settings_width: 320.0,
             rx: None,
             read_running: Arc::new(AtomicBool::new(false)),
+            read_thread: None,
         }
     }
 
-    pub fn ui(&mut self, ctx: &egui::Context) {
+    // Runs every frame whichever tab is shown, so a port left open keeps
+    // logging and an unplugged one comes back while the user is elsewhere.
+    pub fn tick(&mut self, ctx: &egui::Context) {
         // receive message
-        if let Some(rx) = &self.rx {
-            while let Ok(data) = rx.try_recv() {
-                let ts = now_timestamp();
-
-                let display = match self.send_format {
-                    SendFormat::Hex => bytes_to_hex_string(&data),
-                    SendFormat::Ascii => String::from_utf8_lossy(&data)
-                        .replace('\r', "\\r")
-                        .replace('\n', "\\n"),
-                };
-
-                self.logs.push(format!("{} RX <- {}", ts, display));
-
-                ctx.request_repaint();
-            }
+        let messages: Vec<AppMessage> = match &self.rx {
+            Some(rx) => rx.try_iter().collect(),
+            None => Vec::new(),
+        };
+        for msg in messages {
+            self.handle_message(msg);
+            ctx.request_repaint();
+        }
+        self.flush_idle_rx(ctx);
+        self.transfer_tick();
+        if self.tx_transfer.is_some() {
+            ctx.request_repaint();
         }
+        self.try_reconnect(ctx);
+        self.repeat_tick(ctx);
+        self.loopback_tick(ctx);
+        self.at_tick(ctx);
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
         // bottom
         egui::TopBottomPanel::bottom("serial_status").show(ctx, |ui| {
             ui.horizontal(|ui| {
@@ -84,215 +317,1284 @@ impl SerialTool {
             });
         });
 
-        // center
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical(|ui| {
-                // config
-                egui::Frame::group(ui.style()).show(ui, |ui| {
-                    self.ui_config(ui);
-                });
+        // left, drag the edge to give the log more room
+        let settings = egui::SidePanel::left("serial_settings")
+            .resizable(true)
+            .default_width(self.settings_width)
+            .width_range(220.0..=600.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.ui_config(ui);
+                });
+            });
+        self.settings_width = settings.response.rect.width();
+
+        // center
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical(|ui| {
+                // send
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.label("Send");
+                    self.ui_sender(ui);
+                });
+
+                ui.add_space(6.0);
+
+                // log
+                let available_height = ui.available_height();
+
+                egui::Frame::group(ui.style()).show(ui, |ui| {
+                    ui.set_min_height(available_height);
+                    self.ui_logs(ui);
+                });
+            });
+        });
+
+        self.log_diff.ui(ctx);
+        self.plot.ui(ctx);
+    }
+
+    // waits for a lost port to show up again, then reopens it with the same settings
+    fn try_reconnect(&mut self, ctx: &egui::Context) {
+        let Some(name) = self.reconnect_port.clone() else {
+            return;
+        };
+        if !self.auto_reconnect {
+            self.reconnect_port = None;
+            return;
+        }
+
+        ctx.request_repaint_after(RECONNECT_CHECK);
+        if self.last_port_check.elapsed() < RECONNECT_CHECK {
+            return;
+        }
+        self.last_port_check = Instant::now();
+
+        self.port_settings.refresh_ports();
+        if self.port_settings.available_ports.contains(&name) {
+            self.reconnect_port = None;
+            self.port_settings.selected_port = Some(name);
+            self.connect();
+        }
+    }
+
+    fn handle_message(&mut self, msg: AppMessage) {
+        match msg {
+            AppMessage::SerialRx(data) => {
+                self.rx_bytes += data.len() as u64;
+                match &mut self.loopback {
+                    Some(loopback) => loopback.feed(&data),
+                    None if self.paused => self.hold_rx(&data),
+                    None => {
+                        self.append_rx_stream(&data);
+                        self.receive(&data, Instant::now(), now_timestamp());
+                    }
+                }
+            }
+            AppMessage::SerialBufferFull { reads, buffer_size } => {
+                let note = format!(
+                    "RX -- {} reads filled the {} B read buffer, more data may be pending; \
+                     raise Read buffer if the device streams fast",
+                    reads, buffer_size
+                );
+                self.log(LogEntry::info(note), None);
+            }
+            AppMessage::SerialLost(e) => {
+                let port_name = self.port_settings.selected_port.clone();
+                self.log(LogEntry::error(format!("Port lost: {}", e)), None);
+                self.disconnect();
+                self.status = "Disconnected (device removed)".into();
+                if self.auto_reconnect {
+                    self.reconnect_port = port_name;
+                }
+            }
+            AppMessage::BaudScanProgress(baud_rate) => {
+                self.baud_scan = Some(baud_rate);
+                self.status = format!("Auto-detect: listening at {}...", baud_rate);
+            }
+            AppMessage::BaudScanDone(candidates) => {
+                self.baud_scan = None;
+                self.rx = None;
+                self.status = match candidates.first() {
+                    Some(best) if best.bytes > 0 => format!(
+                        "Auto-detect: best guess {} ({:.0}% printable)",
+                        best.baud_rate,
+                        best.score * 100.0
+                    ),
+                    _ => "Auto-detect: no data received at any rate".into(),
+                };
+                self.baud_candidates = candidates;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn ui_config(&mut self, ui: &mut egui::Ui) {
+        ui.set_width(ui.available_width());
+        serial_settings_ui(ui, "serial", &mut self.port_settings);
+
+        ui.horizontal(|ui| {
+            let can_scan = self.port.is_none() && self.baud_scan.is_none();
+            if ui
+                .add_enabled(can_scan, egui::Button::new("Auto-detect"))
+                .on_hover_text("Listen at each standard rate and rank by printable data")
+                .clicked()
+            {
+                self.start_baud_scan();
+            }
+
+            if self.baud_scan.is_some() {
+                ui.spinner();
+                ui.label("Detecting baud rate...");
+            }
+        });
+
+        if self.baud_scan.is_none() && !self.baud_candidates.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Candidates:");
+                for c in &self.baud_candidates {
+                    if c.bytes == 0 {
+                        continue;
+                    }
+                    if ui
+                        .button(format!(
+                            "{} ({:.0}%, {} B)",
+                            c.baud_rate,
+                            c.score * 100.0,
+                            c.bytes
+                        ))
+                        .clicked()
+                    {
+                        self.port_settings.baud_rate = c.baud_rate;
+                    }
+                }
+                if ui.small_button("✖").clicked() {
+                    self.baud_candidates.clear();
+                }
+            });
+        }
+
+        ui.add_space(4.0);
+
+        // -------------------------------
+        // Connect / Disconnect
+        // -------------------------------
+        ui.horizontal(|ui| {
+            if ui
+                .button(egui::RichText::new("Connect").color(egui::Color32::BLUE))
+                .clicked()
+            {
+                self.connect();
+            }
+            if ui
+                .button(egui::RichText::new("Disconnect").color(egui::Color32::RED))
+                .clicked()
+            {
+                // a lost port or a tab switch keeps the file, the user ending
+                // the session closes it
+                self.disconnect();
+                self.close_log_file();
+            }
+
+            ui.checkbox(&mut self.auto_reconnect, "Auto-reconnect")
+                .on_hover_text("Reopen the port when an unplugged device comes back");
+            if self.reconnect_port.is_some() {
+                ui.spinner();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Read buffer");
+            ui.add_enabled(
+                self.port.is_none(),
+                egui::DragValue::new(&mut self.read_buffer_size)
+                    .range(64..=65_536)
+                    .suffix(" B"),
+            )
+            .on_hover_text("Bytes taken per read; raise it when the log notes full reads")
+            .on_disabled_hover_text("Applied on connect");
+        });
+
+        // -------------------------------
+        // Modem control lines
+        // -------------------------------
+        ui.add_enabled_ui(self.port.is_some(), |ui| {
+            ui.horizontal(|ui| {
+                let mut dtr = self.dtr;
+                if ui
+                    .checkbox(&mut dtr, "DTR")
+                    .on_hover_text("Data Terminal Ready")
+                    .changed()
+                {
+                    self.set_control_line(ControlLine::Dtr, dtr);
+                }
+                let mut rts = self.rts;
+                if ui
+                    .checkbox(&mut rts, "RTS")
+                    .on_hover_text("Request To Send")
+                    .changed()
+                {
+                    self.set_control_line(ControlLine::Rts, rts);
+                }
+
+                ui.separator();
+                if ui
+                    .add_enabled(self.loopback.is_none(), egui::Button::new("Loopback test"))
+                    .on_hover_text("With TX jumpered to RX, send 256 bytes and check the echo")
+                    .clicked()
+                {
+                    self.start_loopback();
+                }
+                if self.loopback.is_some() {
+                    ui.spinner();
+                }
+            });
+        });
+
+        // -------------------------------
+        // AT commands
+        // -------------------------------
+        ui.separator();
+        ui.label("AT commands");
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("at_common")
+                .selected_text("Common")
+                .width(80.0)
+                .show_ui(ui, |ui| {
+                    for (command, description) in COMMON_COMMANDS {
+                        if ui
+                            .selectable_label(self.at_command == *command, *command)
+                            .on_hover_text(*description)
+                            .clicked()
+                        {
+                            self.at_command = command.to_string();
+                        }
+                    }
+                });
+            ui.add(egui::TextEdit::singleline(&mut self.at_command).desired_width(120.0));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Timeout");
+            ui.add(
+                egui::DragValue::new(&mut self.at_timeout_ms)
+                    .range(50..=60_000)
+                    .suffix(" ms"),
+            );
+            let can_send = self.port.is_some() && self.at_request.is_none();
+            if ui
+                .add_enabled(
+                    can_send,
+                    egui::Button::new(egui::RichText::new("Send AT").color(egui::Color32::BLUE)),
+                )
+                .on_hover_text("Send with CR and wait for OK or ERROR")
+                .clicked()
+            {
+                self.send_at();
+            }
+            if self.at_request.is_some() {
+                ui.spinner();
+            }
+        });
+        match &self.at_result {
+            Some(Ok(text)) => {
+                ui.colored_label(egui::Color32::DARK_GREEN, format!("✔ {}", text));
+            }
+            Some(Err(text)) => {
+                ui.label(
+                    egui::RichText::new(format!("✖ {}", text))
+                        .strong()
+                        .color(ui.visuals().error_fg_color),
+                );
+            }
+            None => {}
+        }
+    }
+
+    fn set_control_line(&mut self, line: ControlLine, level: bool) {
+        let Some(port) = self.port.clone() else {
+            return;
+        };
+
+        let result = {
+            let mut port = port.lock().unwrap();
+            match line {
+                ControlLine::Dtr => port.write_data_terminal_ready(level),
+                ControlLine::Rts => port.write_request_to_send(level),
+            }
+        };
+
+        match (result, line) {
+            (Ok(_), ControlLine::Dtr) => self.dtr = level,
+            (Ok(_), ControlLine::Rts) => self.rts = level,
+            (Err(e), _) => self.log(
+                LogEntry::error(format!("{:?} -- Set {} failed: {}", line, level, e)),
+                None,
+            ),
+        }
+    }
+
+    pub fn ui_sender(&mut self, ui: &mut egui::Ui) {
+        ui.set_width(ui.available_width());
+
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.send_format, SendFormat::Hex, "HEX");
+            ui.radio_value(&mut self.send_format, SendFormat::Ascii, "ASCII");
+
+            ui.add_enabled_ui(self.send_format == SendFormat::Ascii, |ui| {
+                egui::ComboBox::from_id_salt("line_ending")
+                    .selected_text(self.line_ending.label())
+                    .width(60.0)
+                    .show_ui(ui, |ui| {
+                        for ending in [
+                            LineEnding::None,
+                            LineEnding::Cr,
+                            LineEnding::Lf,
+                            LineEnding::CrLf,
+                        ] {
+                            ui.selectable_value(&mut self.line_ending, ending, ending.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Line ending appended to ASCII sends");
+            });
+
+            ui.separator();
+
+            let input_id = ui.make_persistent_id("serial_input");
+            self.recall_history(ui, input_id);
+            // taken before the input sees it, so no newline is inserted
+            let send_key = self.send_shortcut(ui, input_id);
+            ui.add_sized(
+                [ui.available_width() - 190.0, 24.0],
+                egui::TextEdit::multiline(&mut self.input_text)
+                    .id(input_id)
+                    .hint_text(match self.send_format {
+                        SendFormat::Hex => "48 65 6C 6C 6F",
+                        SendFormat::Ascii => "Hello",
+                    }),
+            );
+
+            if ui
+                .button(egui::RichText::new("Send").color(egui::Color32::BLUE))
+                .on_hover_text(if self.enter_sends {
+                    "Enter or Ctrl+Enter"
+                } else {
+                    "Ctrl+Enter"
+                })
+                .clicked()
+                || send_key
+            {
+                self.send();
+            }
+
+            if ui.button("Clear").clicked() {
+                self.clear_logs();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Repeat every");
+            ui.add(
+                egui::DragValue::new(&mut self.repeat_ms)
+                    .range(10..=3_600_000)
+                    .suffix(" ms"),
+            );
+
+            if !self.repeating {
+                if ui
+                    .add_enabled(self.port.is_some(), egui::Button::new("▶ Repeat"))
+                    .clicked()
+                {
+                    self.repeating = true;
+                    self.last_repeat = Instant::now();
+                    self.send();
+                }
+            } else if ui
+                .button(egui::RichText::new("⏹ Stop").color(egui::Color32::RED))
+                .clicked()
+            {
+                self.repeating = false;
+            }
+
+            if let Some(transfer) = self.tx_transfer.as_ref().filter(|t| t.chunked()) {
+                ui.separator();
+                ui.add(
+                    egui::ProgressBar::new(transfer.sent() as f32 / transfer.total() as f32)
+                        .desired_width(160.0)
+                        .text(format!("{} / {} B", transfer.sent(), transfer.total())),
+                );
+                if ui.button("Cancel").clicked() {
+                    self.cancel_transfer();
+                }
+            }
+
+            ui.separator();
+            ui.checkbox(&mut self.enter_sends, "Enter sends")
+                .on_hover_text("Off: Enter adds a line. Ctrl+Enter sends either way");
+        });
+
+        self.ui_macros(ui);
+    }
+
+    // Up/Down while the input has focus; left alone for multi-line input,
+    // where the arrows still move the cursor between lines
+    fn recall_history(&mut self, ui: &mut egui::Ui, input_id: egui::Id) {
+        if !ui.memory(|m| m.has_focus(input_id)) || self.input_text.contains('\n') {
+            return;
+        }
+
+        let (up, down) = ui.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            )
+        });
+        let recalled = if up {
+            self.send_history.older(&self.input_text)
+        } else if down {
+            self.send_history.newer()
+        } else {
+            None
+        };
+        if let Some(text) = recalled {
+            self.input_text = text;
+        }
+    }
+
+    fn send_shortcut(&self, ui: &mut egui::Ui, input_id: egui::Id) -> bool {
+        if !ui.memory(|m| m.has_focus(input_id)) {
+            return false;
+        }
+        ui.input_mut(|i| {
+            // consume_key ignores extra Shift / Alt, so Shift+Enter has to be
+            // ruled out here to still reach the TextEdit as a newline
+            i.consume_key(egui::Modifiers::COMMAND, egui::Key::Enter)
+                || (self.enter_sends
+                    && i.modifiers.is_none()
+                    && i.consume_key(egui::Modifiers::NONE, egui::Key::Enter))
+        })
+    }
+
+    fn ui_macros(&mut self, ui: &mut egui::Ui) {
+        let mut send = None;
+        let mut edit = None;
+        let mut delete = None;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Macros");
+            for (i, m) in self.macros.iter().enumerate() {
+                let button = ui
+                    .button(&m.name)
+                    .on_hover_text(format!("{}\nRight-click to edit", m.payload));
+                if button.clicked() {
+                    send = Some(i);
+                }
+                button.context_menu(|ui| {
+                    if ui.button("Edit").clicked() {
+                        edit = Some(i);
+                        ui.close();
+                    }
+                    if ui.button("Delete").clicked() {
+                        delete = Some(i);
+                        ui.close();
+                    }
+                });
+            }
+
+            if ui.button("+").on_hover_text("New macro").clicked() {
+                self.macro_draft = Some(MacroDraft {
+                    index: None,
+                    serial_macro: SerialMacro {
+                        name: String::new(),
+                        payload: self.input_text.clone(),
+                        format: self.send_format,
+                    },
+                });
+            }
+        });
+
+        if let Some(i) = send {
+            let m = self.macros[i].clone();
+            self.send_payload(&m.payload, m.format);
+        }
+        if let Some(i) = edit {
+            self.macro_draft = Some(MacroDraft {
+                index: Some(i),
+                serial_macro: self.macros[i].clone(),
+            });
+        }
+        if let Some(i) = delete {
+            self.macros.remove(i);
+            self.macro_draft = None;
+            self.save_macros();
+        }
+
+        self.ui_macros_error(ui);
+        self.ui_macro_draft(ui);
+    }
+
+    fn ui_macros_error(&mut self, ui: &mut egui::Ui) {
+        let Some(error) = &self.macros_error else {
+            return;
+        };
+
+        let mut reload = false;
+        let mut overwrite = false;
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(ui.visuals().error_fg_color, format!("✖ {}", error));
+            reload = ui
+                .button("Reload")
+                .on_hover_text("Read the file again, e.g. after fixing it by hand")
+                .clicked();
+            overwrite = ui
+                .button("Overwrite")
+                .on_hover_text("Replace the file with the macros shown here")
+                .clicked();
+        });
+
+        if reload {
+            match macros::load(MACROS_FILE) {
+                Ok(macros) => {
+                    self.macros = macros;
+                    self.macros_error = None;
+                    self.macro_draft = None;
+                    self.status = format!("Loaded {}", MACROS_FILE);
+                }
+                Err(e) => {
+                    let error = format!("Load {} failed: {}", MACROS_FILE, e);
+                    self.status = error.clone();
+                    self.macros_error = Some(error);
+                }
+            }
+        }
+        if overwrite {
+            self.macros_error = None;
+            self.save_macros();
+        }
+    }
+
+    fn ui_macro_draft(&mut self, ui: &mut egui::Ui) {
+        let Some(draft) = &mut self.macro_draft else {
+            return;
+        };
+
+        let mut done = None;
+        ui.horizontal(|ui| {
+            let m = &mut draft.serial_macro;
+            ui.label("Name");
+            ui.add(egui::TextEdit::singleline(&mut m.name).desired_width(80.0));
+            ui.label("Payload");
+            ui.add(egui::TextEdit::singleline(&mut m.payload).desired_width(160.0));
+            ui.radio_value(&mut m.format, SendFormat::Hex, "HEX");
+            ui.radio_value(&mut m.format, SendFormat::Ascii, "ASCII");
+
+            let valid = !m.name.trim().is_empty()
+                && (m.format == SendFormat::Ascii || parse_hex_string(&m.payload).is_ok());
+            if ui
+                .add_enabled(
+                    valid,
+                    egui::Button::new(egui::RichText::new("Save").color(egui::Color32::BLUE)),
+                )
+                .on_disabled_hover_text("Needs a name and, for HEX, valid hex")
+                .clicked()
+            {
+                done = Some(true);
+            }
+            if ui.button("Cancel").clicked() {
+                done = Some(false);
+            }
+        });
+
+        match done {
+            Some(true) => {
+                let Some(draft) = self.macro_draft.take() else {
+                    return;
+                };
+                match draft.index {
+                    Some(i) if i < self.macros.len() => self.macros[i] = draft.serial_macro,
+                    _ => self.macros.push(draft.serial_macro),
+                }
+                self.save_macros();
+            }
+            Some(false) => self.macro_draft = None,
+            None => {}
+        }
+    }
+
+    fn save_macros(&mut self) {
+        if self.macros_error.is_some() {
+            self.status = format!(
+                "Macros not saved: reload or overwrite the unreadable {} first",
+                MACROS_FILE
+            );
+            return;
+        }
+        if let Err(e) = macros::save(MACROS_FILE, &self.macros) {
+            self.status = format!("Save {} failed: {}", MACROS_FILE, e);
+        }
+    }
+
+    // re-sends the current input at the repeat interval
+    fn repeat_tick(&mut self, ctx: &egui::Context) {
+        if !self.repeating {
+            return;
+        }
+        if self.port.is_none() {
+            self.repeating = false;
+            return;
+        }
+
+        let interval = Duration::from_millis(self.repeat_ms);
+        let elapsed = self.last_repeat.elapsed();
+        // a slow line may still be busy with the last one
+        if elapsed >= interval && self.tx_transfer.is_none() {
+            self.last_repeat = Instant::now();
+            self.send();
+            ctx.request_repaint_after(interval);
+        } else {
+            // past the interval while busy, check back shortly
+            let wait = interval.saturating_sub(elapsed);
+            ctx.request_repaint_after(wait.max(Duration::from_millis(10)));
+        }
+    }
+
+    fn hold_rx(&mut self, data: &[u8]) {
+        self.paused_rx.push_back(HeldRx {
+            at: Instant::now(),
+            ts: now_timestamp(),
+            data: data.to_vec(),
+        });
+        self.paused_bytes += data.len();
+        while self.paused_bytes > PAUSE_BUFFER_MAX {
+            let Some(oldest) = self.paused_rx.pop_front() else {
+                break;
+            };
+            self.paused_bytes -= oldest.data.len();
+            self.paused_dropped += oldest.data.len();
+        }
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        if paused {
+            return;
+        }
+        if self.paused_dropped > 0 {
+            let dropped = std::mem::take(&mut self.paused_dropped);
+            self.log(
+                LogEntry::error(format!(
+                    "Pause -- {} bytes dropped, hold buffer was full",
+                    dropped
+                )),
+                None,
+            );
+        }
+        // read by read with their arrival times, so entries are split and
+        // stamped the way they would have been live
+        self.paused_bytes = 0;
+        let idle = Duration::from_millis(self.rx_idle_ms);
+        let mut last_ts = None;
+        while let Some(held) = self.paused_rx.pop_front() {
+            if !self.rx_buffer.is_empty() && held.at.duration_since(self.rx_last) >= idle {
+                let rest = std::mem::take(&mut self.rx_buffer);
+                let ts = last_ts.take().unwrap_or_else(|| held.ts.clone());
+                self.log_rx(&rest, ts);
+            }
+            self.append_rx_stream(&held.data);
+            last_ts = Some(held.ts.clone());
+            self.receive(&held.data, held.at, held.ts);
+        }
+    }
+
+    // `at` / `ts` are when the data arrived, earlier than now for held reads
+    fn receive(&mut self, data: &[u8], at: Instant, ts: String) {
+        let delimiter = match self.rx_delimiter {
+            RxDelimiter::None => {
+                self.log_rx(data, ts);
+                return;
+            }
+            RxDelimiter::Newline => b'\n',
+            RxDelimiter::Custom => self.rx_custom_delimiter,
+        };
+
+        self.rx_buffer.extend_from_slice(data);
+        self.rx_last = at;
+
+        while let Some(pos) = self.rx_buffer.iter().position(|b| *b == delimiter) {
+            let line: Vec<u8> = self.rx_buffer.drain(..=pos).collect();
+            self.log_rx(&line, ts.clone());
+        }
+    }
+
+    // a delimiter that never comes must not hold data forever
+    fn flush_idle_rx(&mut self, ctx: &egui::Context) {
+        if self.rx_buffer.is_empty() {
+            return;
+        }
+
+        let idle = Duration::from_millis(self.rx_idle_ms);
+        let elapsed = self.rx_last.elapsed();
+        if elapsed >= idle || self.rx_delimiter == RxDelimiter::None {
+            let rest = std::mem::take(&mut self.rx_buffer);
+            self.log_rx(&rest, now_timestamp());
+        } else {
+            ctx.request_repaint_after(idle - elapsed);
+        }
+    }
+
+    fn log_rx(&mut self, data: &[u8], ts: String) {
+        let display = match self.rx_display {
+            RxDisplay::Hex => self.rx_hex_style.format(data),
+            RxDisplay::Ascii => String::from_utf8_lossy(data)
+                .replace('\r', "\\r")
+                .replace('\n', "\\n"),
+            RxDisplay::Both | RxDisplay::Dump => hexdump(data),
+        };
+
+        self.log(
+            LogEntry {
+                ts,
+                ..LogEntry::rx(display)
+            },
+            Some(data),
+        );
+        if self.nmea {
+            self.decode_nmea(data);
+        }
+        if self.plot.open {
+            self.plot.push_line(&String::from_utf8_lossy(data));
+        }
+        if let Some(request) = &mut self.at_request {
+            // a custom delimiter can leave several lines in one entry
+            let text = String::from_utf8_lossy(data);
+            if let Some(outcome) = text.split('\n').find_map(|line| request.feed(line)) {
+                self.finish_at(outcome);
+            }
+        }
+    }
+
+    // bad checksums still replace the previous sentence, flagged in the table
+    fn decode_nmea(&mut self, data: &[u8]) {
+        let Some(sentence) = nmea::parse(&String::from_utf8_lossy(data)) else {
+            return;
+        };
+        if matches!(sentence.checksum, Checksum::Invalid { .. }) {
+            self.nmea_bad_checksums += 1;
+        }
+        self.nmea_sentences
+            .insert(sentence.address.clone(), sentence);
+    }
+
+    fn append_rx_stream(&mut self, data: &[u8]) {
+        self.rx_stream.extend_from_slice(data);
+        if self.rx_stream.len() > RX_STREAM_MAX {
+            // whole rows only, so the offsets stay multiples of 16
+            let excess = (self.rx_stream.len() - RX_STREAM_MAX).div_ceil(16) * 16;
+            self.rx_stream.drain(..excess);
+            self.rx_stream_offset += excess;
+        }
+    }
 
-                ui.add_space(6.0);
+    pub fn log(&mut self, entry: LogEntry, raw: Option<&[u8]>) {
+        if let Some(file) = &mut self.log_file {
+            if let Err(e) = file.write(&entry, raw) {
+                let note = format!("Log file {} closed, write failed: {}", file.path, e);
+                self.status = note.clone();
+                self.log_file = None;
+                self.logs.push_back(LogEntry::error(note));
+            }
+        }
+        self.logs.push_back(entry);
+        self.trim_logs();
+    }
 
-                // send
-                egui::Frame::group(ui.style()).show(ui, |ui| {
-                    ui.label("Send");
-                    self.ui_sender(ui);
-                });
+    // the closing line goes into the file too, so it shows where a capture ended
+    pub fn close_log_file(&mut self) {
+        let Some(path) = self.log_file.as_ref().map(|file| file.path.clone()) else {
+            return;
+        };
+        self.log(LogEntry::info(format!("Log file {} closed", path)), None);
+        self.log_file = None;
+        self.status = format!("Stopped logging to {}", path);
+    }
 
-                ui.add_space(6.0);
+    pub fn trim_logs(&mut self) {
+        while self.logs.len() > self.max_log_lines {
+            self.logs.pop_front();
+        }
+    }
 
-                // log
-                let available_height = ui.available_height();
+    fn set_log_to_file(&mut self, on: bool) {
+        if !on {
+            self.close_log_file();
+            return;
+        }
+        match LogFile::open(&self.log_path) {
+            Ok(file) => {
+                self.status = format!("Logging to {}", file.path);
+                self.log_file = Some(file);
+            }
+            Err(e) => self.status = format!("Open {} failed: {}", self.log_path, e),
+        }
+    }
 
-                egui::Frame::group(ui.style()).show(ui, |ui| {
-                    ui.set_min_height(available_height);
-                    self.ui_logs(ui);
-                });
-            });
-        });
+    pub fn clear_logs(&mut self) {
+        self.logs.clear();
+        self.rx_buffer.clear();
+        self.rx_stream.clear();
+        self.rx_stream_offset = 0;
+        self.paused_rx.clear();
+        self.paused_bytes = 0;
+        self.paused_dropped = 0;
+        self.nmea_sentences.clear();
+        self.nmea_bad_checksums = 0;
+        self.tx_bytes = 0;
+        self.rx_bytes = 0;
     }
 
-    pub fn ui_config(&mut self, ui: &mut egui::Ui) {
+    pub fn ui_logs(&mut self, ui: &mut egui::Ui) {
         ui.set_width(ui.available_width());
-        // -------------------------------
-        // Refresh + Port
-        // -------------------------------
-        ui.horizontal(|ui| {
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Logs");
+            if ui.button("Diff Logs…").clicked() {
+                self.log_diff.open = true;
+            }
             if ui
-                .button(egui::RichText::new("Refresh Ports").color(egui::Color32::BLUE))
+                .button("Plot…")
+                .on_hover_text("Plot numbers from the RX lines")
                 .clicked()
             {
-                self.available_ports = serialport::available_ports().unwrap_or_default();
-                self.selected_port = Some("Select Port".to_string());
+                self.plot.open = true;
+                // one value per line, frames would cut numbers apart
+                if self.rx_delimiter == RxDelimiter::None {
+                    self.rx_delimiter = RxDelimiter::Newline;
+                }
             }
 
-            egui::ComboBox::from_label("")
-                .width(220.0)
-                .selected_text(
-                    self.selected_port
-                        .clone()
-                        .unwrap_or_else(|| "Select Port".into()),
-                )
-                .show_ui(ui, |ui| {
-                    for p in &self.available_ports {
-                        if ui
-                            .selectable_label(
-                                self.selected_port.as_deref() == Some(&p.port_name),
-                                &p.port_name,
-                            )
-                            .clicked()
-                        {
-                            self.selected_port = Some(p.port_name.clone());
-                        }
-                    }
-                });
-        });
-
-        ui.add_space(6.0);
-
-        // -------------------------------
-        // Baud + DataBits + Parity + StopBits + FlowControl
-        // -------------------------------
-        ui.horizontal(|ui| {
-            ui.label(egui::RichText::new("Baud rate:").strong());
-            ui.add(
-                egui::DragValue::new(&mut self.baud_rate)
-                    .speed(100)
-                    .range(1200..=921600),
-            );
+            ui.separator();
+            ui.radio_value(&mut self.log_layout, LogLayout::Combined, "Combined");
+            ui.radio_value(&mut self.log_layout, LogLayout::Split, "TX | RX");
 
             ui.separator();
+            ui.label("RX");
+            ui.radio_value(&mut self.rx_display, RxDisplay::Hex, "HEX");
+            ui.radio_value(&mut self.rx_display, RxDisplay::Ascii, "ASCII");
+            ui.radio_value(&mut self.rx_display, RxDisplay::Both, "Both");
+            ui.radio_value(&mut self.rx_display, RxDisplay::Dump, "Dump")
+                .on_hover_text("Hexdump of everything received, with offsets");
+            if self.rx_display == RxDisplay::Hex {
+                egui::ComboBox::from_id_salt("rx_hex_style")
+                    .width(90.0)
+                    .selected_text(self.rx_hex_style.label())
+                    .show_ui(ui, |ui| {
+                        for style in [HexStyle::Spaced, HexStyle::Compact, HexStyle::Prefixed] {
+                            ui.selectable_value(&mut self.rx_hex_style, style, style.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Hex separator");
+            }
 
-            ui.label(egui::RichText::new("Data bits:").strong());
-            ui.radio_value(&mut self.data_bits, serialport::DataBits::Five, "5");
-            ui.radio_value(&mut self.data_bits, serialport::DataBits::Six, "6");
-            ui.radio_value(&mut self.data_bits, serialport::DataBits::Seven, "7");
-            ui.radio_value(&mut self.data_bits, serialport::DataBits::Eight, "8");
+            ui.label("Split");
+            egui::ComboBox::from_id_salt("rx_delimiter")
+                .width(70.0)
+                .selected_text(match self.rx_delimiter {
+                    RxDelimiter::None => "None",
+                    RxDelimiter::Newline => "\\n",
+                    RxDelimiter::Custom => "Custom",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.rx_delimiter, RxDelimiter::None, "None");
+                    ui.selectable_value(&mut self.rx_delimiter, RxDelimiter::Newline, "\\n");
+                    ui.selectable_value(&mut self.rx_delimiter, RxDelimiter::Custom, "Custom");
+                });
+            if self.rx_delimiter == RxDelimiter::Custom {
+                ui.add(
+                    egui::DragValue::new(&mut self.rx_custom_delimiter)
+                        .hexadecimal(2, false, true)
+                        .prefix("0x"),
+                )
+                .on_hover_text("Delimiter byte");
+            }
+            if self.rx_delimiter != RxDelimiter::None {
+                ui.add(
+                    egui::DragValue::new(&mut self.rx_idle_ms)
+                        .range(10..=10_000)
+                        .suffix(" ms"),
+                )
+                .on_hover_text("Flush an unterminated line after this idle time");
+            }
+            if ui
+                .checkbox(&mut self.nmea, "NMEA")
+                .on_hover_text("Decode GPS sentences from the RX lines")
+                .changed()
+                && self.nmea
+                && self.rx_delimiter == RxDelimiter::None
+            {
+                // sentences are CRLF terminated, frames would cut them apart
+                self.rx_delimiter = RxDelimiter::Newline;
+            }
 
             ui.separator();
+            ui.checkbox(&mut self.show_timestamps, "Timestamps");
 
-            ui.label(egui::RichText::new("Parity:").strong());
-            ui.radio_value(&mut self.parity, serialport::Parity::None, "None");
-            ui.radio_value(&mut self.parity, serialport::Parity::Odd, "Odd");
-            ui.radio_value(&mut self.parity, serialport::Parity::Even, "Even");
+            ui.label("Keep");
+            if ui
+                .add(
+                    egui::DragValue::new(&mut self.max_log_lines)
+                        .range(100..=1_000_000)
+                        .suffix(" lines"),
+                )
+                .on_hover_text("Older lines are dropped from view, not from the log file")
+                .changed()
+            {
+                self.trim_logs();
+            }
 
             ui.separator();
-
-            ui.label(egui::RichText::new("Stop bits:").strong());
-            ui.radio_value(&mut self.stop_bits, serialport::StopBits::One, "1");
-            ui.radio_value(&mut self.stop_bits, serialport::StopBits::Two, "2");
+            let mut to_file = self.log_file.is_some();
+            if ui.checkbox(&mut to_file, "Log to file").changed() {
+                self.set_log_to_file(to_file);
+            }
+            ui.add_enabled(
+                self.log_file.is_none(),
+                egui::TextEdit::singleline(&mut self.log_path).desired_width(160.0),
+            );
 
             ui.separator();
+            ui.monospace(format!("TX: {} B  RX: {} B", self.tx_bytes, self.rx_bytes));
 
-            ui.label(egui::RichText::new("Flow control:").strong());
-            ui.radio_value(
-                &mut self.flow_control,
-                serialport::FlowControl::None,
-                "None",
-            );
-            ui.radio_value(
-                &mut self.flow_control,
-                serialport::FlowControl::Software,
-                "Software",
-            );
-            ui.radio_value(
-                &mut self.flow_control,
-                serialport::FlowControl::Hardware,
-                "Hardware",
-            );
+            ui.separator();
+            let (label, hint) = if self.paused {
+                ("▶ Resume", "Show the data received while paused")
+            } else {
+                ("⏸ Pause", "Freeze the log, RX is kept and shown on resume")
+            };
+            if ui.button(label).on_hover_text(hint).clicked() {
+                self.set_paused(!self.paused);
+            }
+            if self.paused {
+                ui.weak(format!("{} B held", self.paused_bytes));
+            }
         });
 
-        ui.add_space(4.0);
-
-        // -------------------------------
-        // Connect / Disconnect
-        // -------------------------------
         ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.log_filter)
+                    .hint_text("Search...")
+                    .desired_width(200.0),
+            );
             if ui
-                .button(egui::RichText::new("Connect").color(egui::Color32::BLUE))
-                .clicked()
-            {
-                self.connect();
-            }
-            if ui
-                .button(egui::RichText::new("Disconnect").color(egui::Color32::RED))
+                .add_enabled(!self.log_filter.is_empty(), egui::Button::new("✖"))
                 .clicked()
             {
-                self.disconnect();
+                self.log_filter.clear();
             }
-        });
-    }
-
-    pub fn ui_sender(&mut self, ui: &mut egui::Ui) {
-        ui.set_width(ui.available_width());
-
-        ui.horizontal(|ui| {
-            ui.radio_value(&mut self.send_format, SendFormat::Hex, "HEX");
-            ui.radio_value(&mut self.send_format, SendFormat::Ascii, "ASCII");
 
             ui.separator();
+            ui.selectable_value(&mut self.log_filter_direction, None, "All");
+            ui.selectable_value(&mut self.log_filter_direction, Some(Direction::Tx), "TX");
+            ui.selectable_value(&mut self.log_filter_direction, Some(Direction::Rx), "RX");
 
-            ui.add_sized(
-                [ui.available_width() - 140.0, 24.0],
-                egui::TextEdit::multiline(&mut self.input_text).hint_text(match self.send_format {
-                    SendFormat::Hex => "48 65 6C 6C 6F",
-                    SendFormat::Ascii => "Hello",
-                }),
-            );
+            if self.filtering() {
+                let needle = self.log_filter.to_ascii_lowercase();
+                let shown = self
+                    .logs
+                    .iter()
+                    .filter(|log| log.matches(self.log_filter_direction, &needle))
+                    .count();
+                ui.weak(format!("{} / {}", shown, self.logs.len()));
+            }
 
+            ui.separator();
             if ui
-                .button(egui::RichText::new("Send").color(egui::Color32::BLUE))
+                .button("Copy all")
+                .on_hover_text("Copy the shown lines to the clipboard")
                 .clicked()
             {
-                self.send();
+                ui.ctx().copy_text(self.shown_text());
             }
         });
-    }
 
-    pub fn ui_logs(&mut self, ui: &mut egui::Ui) {
-        ui.set_width(ui.available_width());
+        if self.nmea {
+            self.ui_nmea(ui);
+        }
 
-        ui.label("Logs");
+        if self.rx_display == RxDisplay::Dump {
+            self.ui_rx_dump(ui);
+            return;
+        }
 
         egui::ScrollArea::vertical()
             .stick_to_bottom(true)
             .show(ui, |ui| {
                 ui.set_width(ui.available_width());
+                let needle = self.log_filter.to_ascii_lowercase();
+                match self.log_layout {
+                    LogLayout::Combined => {
+                        for log in &self.logs {
+                            if !log.matches(self.log_filter_direction, &needle) {
+                                continue;
+                            }
+                            let line = log.render(self.show_timestamps);
+                            let color = direction_color(ui.visuals(), log.direction);
+                            let text: egui::WidgetText = if needle.is_empty() {
+                                egui::RichText::new(&line).monospace().color(color).into()
+                            } else {
+                                highlight(ui, &line, &needle, color).into()
+                            };
+                            // drag to select part of it, right-click for the whole line
+                            ui.add(egui::Label::new(text).selectable(true))
+                                .context_menu(|ui| {
+                                    if ui.button("Copy line").clicked() {
+                                        ui.ctx().copy_text(line.clone());
+                                        ui.close();
+                                    }
+                                });
+                        }
+                    }
+                    LogLayout::Split => self.ui_split_logs(ui, &needle),
+                }
+            });
+    }
+
+    fn ui_nmea(&self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.horizontal(|ui| {
+                ui.label("NMEA");
+                if self.nmea_bad_checksums > 0 {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        format!("{} bad checksums", self.nmea_bad_checksums),
+                    );
+                }
+            });
+            if self.nmea_sentences.is_empty() {
+                ui.weak("No sentences received yet");
+                return;
+            }
+
+            egui::Grid::new("serial_nmea")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for sentence in self.nmea_sentences.values() {
+                        ui.monospace(&sentence.address);
+                        match sentence.checksum {
+                            Checksum::Valid => {
+                                ui.label("✔");
+                            }
+                            Checksum::Missing => {
+                                ui.weak("-").on_hover_text("No checksum sent");
+                            }
+                            Checksum::Invalid { stated, computed } => {
+                                ui.colored_label(ui.visuals().error_fg_color, "✖ checksum")
+                                    .on_hover_text(format!(
+                                        "Sentence says {:02X}, data gives {:02X}",
+                                        stated, computed
+                                    ));
+                            }
+                        }
+                        let fields: Vec<String> = sentence
+                            .fields
+                            .iter()
+                            .map(|(label, value)| match *label {
+                                "" => value.clone(),
+                                _ => format!("{}: {}", label, value),
+                            })
+                            .collect();
+                        let separator = if sentence.fields.iter().any(|(l, _)| l.is_empty()) {
+                            ","
+                        } else {
+                            "  "
+                        };
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(fields.join(separator)).monospace(),
+                            )
+                            .wrap(),
+                        );
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    // only the visible rows are formatted, the stream can be 4096 rows long
+    fn ui_rx_dump(&self, ui: &mut egui::Ui) {
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let rows = self.rx_stream.len().div_ceil(16);
+        let color = direction_color(ui.visuals(), Direction::Rx);
+        egui::ScrollArea::vertical()
+            .id_salt("serial_rx_dump")
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, rows, |ui, range| {
+                ui.set_width(ui.available_width());
+                for row in range {
+                    let start = row * 16;
+                    let chunk = &self.rx_stream[start..(start + 16).min(self.rx_stream.len())];
+                    let line = hexdump_row(self.rx_stream_offset + start, chunk);
+                    ui.add(
+                        egui::Label::new(egui::RichText::new(line).monospace().color(color))
+                            .selectable(true),
+                    );
+                }
+            });
+    }
+
+    // what "Copy all" puts on the clipboard: the filtered lines as rendered
+    fn shown_text(&self) -> String {
+        if self.rx_display == RxDisplay::Dump {
+            return self
+                .rx_stream
+                .chunks(16)
+                .enumerate()
+                .map(|(row, chunk)| hexdump_row(self.rx_stream_offset + row * 16, chunk))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+        let needle = self.log_filter.to_ascii_lowercase();
+        self.logs
+            .iter()
+            .filter(|log| log.matches(self.log_filter_direction, &needle))
+            .map(|log| log.render(self.show_timestamps))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn filtering(&self) -> bool {
+        !self.log_filter.is_empty() || self.log_filter_direction.is_some()
+    }
+
+    fn ui_split_logs(&self, ui: &mut egui::Ui, needle: &str) {
+        let ts_width = if self.show_timestamps { 100.0 } else { 0.0 };
+        let col_width = ((ui.available_width() - ts_width) / 2.0 - 16.0).max(80.0);
+
+        egui::Grid::new("serial_split_logs")
+            .striped(true)
+            .num_columns(3)
+            .min_col_width(ts_width)
+            .show(ui, |ui| {
+                if self.show_timestamps {
+                    ui.label(egui::RichText::new("Time").strong());
+                } else {
+                    ui.label("");
+                }
+                ui.add_sized(
+                    [col_width, 0.0],
+                    egui::Label::new(egui::RichText::new("TX").strong()),
+                );
+                ui.add_sized(
+                    [col_width, 0.0],
+                    egui::Label::new(egui::RichText::new("RX").strong()),
+                );
+                ui.end_row();
+
                 for log in &self.logs {
-                    ui.monospace(log);
+                    if !log.matches(self.log_filter_direction, needle) {
+                        continue;
+                    }
+                    if self.show_timestamps {
+                        ui.label(egui::RichText::new(&log.ts).monospace().weak());
+                    } else {
+                        ui.label("");
+                    }
+                    let color = direction_color(ui.visuals(), log.direction);
+                    let data: egui::WidgetText = if needle.is_empty() {
+                        egui::RichText::new(&log.text)
+                            .monospace()
+                            .color(color)
+                            .into()
+                    } else {
+                        highlight(ui, &log.text, needle, color).into()
+                    };
+                    match log.direction {
+                        Direction::Tx => {
+                            ui.add(egui::Label::new(data).wrap().selectable(true));
+                            ui.label("");
+                        }
+                        Direction::Rx => {
+                            ui.label("");
+                            ui.add(egui::Label::new(data).wrap().selectable(true));
+                        }
+                        Direction::Info | Direction::Error => {
+                            ui.label(data);
+                            ui.label("");
+                        }
+                    }
+                    ui.end_row();
                 }
             });
     }
 
+    pub fn start_baud_scan(&mut self) {
+        let Some(port_name) = self.port_settings.selected_port.clone() else {
+            self.status = "No port selected".into();
+            return;
+        };
+
+        let (tx, rx) = mpsc::channel();
+        self.rx = Some(rx);
+        self.baud_scan = Some(baud::STANDARD_BAUD_RATES[0]);
+        self.baud_candidates.clear();
+
+        let settings = &self.port_settings;
+        baud::start_scan(
+            port_name,
+            settings.data_bits,
+            settings.parity,
+            settings.stop_bits,
+            tx,
+        );
+    }
+
     pub fn connect(&mut self) {
-        let Some(port_name) = &self.selected_port else {
+        let Some(port_name) = self.port_settings.selected_port.clone() else {
             self.status = "No port selected".into();
             return;
         };
+        if self.port_settings.baud_rate == 0 {
+            self.status = "Invalid baud rate: 0".into();
+            return;
+        }
 
-        match serialport::new(port_name, self.baud_rate)
-            .data_bits(self.data_bits)
-            .parity(self.parity)
-            .stop_bits(self.stop_bits)
+        match self
+            .port_settings
+            .builder(&port_name)
             .timeout(Duration::from_millis(100))
             .open()
         {
             Ok(port) => {
                 let port = Arc::new(Mutex::new(port));
+                // a stored size may predate the limits of the setting
+                self.read_buffer_size = self.read_buffer_size.clamp(64, 65_536);
 
                 let (tx, rx) = mpsc::channel();
 
-                Self::start_read_thread(port.clone(), tx, self.read_running.clone());
+                self.read_thread = Some(Self::start_read_thread(
+                    port.clone(),
+                    tx,
+                    self.read_running.clone(),
+                    self.read_buffer_size,
+                ));
 
                 self.port = Some(port);
                 self.rx = Some(rx);
+                // opening a port normally asserts both lines
+                self.dtr = true;
+                self.rts = true;
 
                 self.status = format!("Connected: {}", port_name);
-                self.logs.push("Connected".into());
+                self.log(LogEntry::info("Connected"), None);
             }
             Err(e) => {
-                self.status = format!("Connect failed: {e}");
+                self.status = format!(
+                    "Connect failed at {} baud: {e}",
+                    self.port_settings.baud_rate
+                );
             }
         }
     }
@@ -300,58 +1602,262 @@ impl SerialTool {
     pub fn disconnect(&mut self) {
         self.read_running.store(false, Ordering::SeqCst);
 
+        // the reader holds a clone of the port; wait for it (at most one read
+        // timeout) so the device is really closed once we return
+        if let Some(handle) = self.read_thread.take() {
+            let _ = handle.join();
+        }
+
         self.port = None;
         self.rx = None;
+        self.reconnect_port = None;
+        self.repeating = false;
+        self.loopback = None;
+        self.at_request = None;
+        self.cancel_transfer();
         self.status = "Disconnected".into();
     }
 
     pub fn send(&mut self) {
-        let Some(port) = &self.port else {
-            self.logs.push("TX -- Not connected".into());
+        let text = self.input_text.clone();
+        self.send_history.push(&text);
+        self.send_payload(&text, self.send_format);
+    }
+
+    // shared by the input box and macros, ASCII gets the line ending appended
+    pub fn send_payload(&mut self, text: &str, format: SendFormat) {
+        if self.port.is_none() {
+            self.log(LogEntry::error("TX -- Not connected"), None);
             return;
-        };
+        }
+        if self.tx_busy() {
+            return;
+        }
 
-        let bytes = match self.send_format {
-            SendFormat::Hex => match parse_hex_string(&self.input_text) {
+        let bytes = match format {
+            SendFormat::Hex => match parse_hex_string(text) {
                 Ok(b) => b,
                 Err(e) => {
-                    self.logs.push(format!("TX -- HEX error: {}", e));
+                    self.log(LogEntry::error(format!("TX -- Invalid hex: {}", e)), None);
                     return;
                 }
             },
-            SendFormat::Ascii => self.input_text.as_bytes().to_vec(),
+            SendFormat::Ascii => {
+                let mut bytes = text.as_bytes().to_vec();
+                bytes.extend_from_slice(self.line_ending.bytes());
+                bytes
+            }
+        };
+
+        let display = match format {
+            SendFormat::Hex => bytes_to_hex_string(&bytes),
+            SendFormat::Ascii => String::from_utf8_lossy(&bytes)
+                .replace('\r', "\\r")
+                .replace('\n', "\\n"),
+        };
+
+        // short sends are out before this returns, long ones continue each frame
+        self.tx_transfer = Some(Transfer::new(bytes, display));
+        self.transfer_tick();
+    }
+
+    // one send at a time, a second one would interleave its bytes with the first
+    fn tx_busy(&mut self) -> bool {
+        let Some(transfer) = &self.tx_transfer else {
+            return false;
+        };
+        let busy = format!(
+            "TX -- Busy, {} of {} bytes of the last send still going out",
+            transfer.total() - transfer.sent(),
+            transfer.total()
+        );
+        self.log(LogEntry::error(busy), None);
+        true
+    }
+
+    fn transfer_tick(&mut self) {
+        let (Some(port), Some(transfer)) = (self.port.clone(), &mut self.tx_transfer) else {
+            return;
         };
 
-        let mut port = port.lock().unwrap();
-        if let Err(e) = port.write_all(&bytes) {
-            self.logs.push(format!("TX -- Send failed: {}", e));
+        let started = Instant::now();
+        let outcome = loop {
+            let step = transfer.step(&mut **port.lock().unwrap());
+            match step {
+                Ok(false) if started.elapsed() < TX_FRAME_BUDGET => continue,
+                step => break step,
+            }
+        };
+        if outcome == Ok(false) {
             return;
         }
 
-        let ts = now_timestamp();
-        let display = match self.send_format {
-            SendFormat::Hex => bytes_to_hex_string(&bytes),
-            SendFormat::Ascii => String::from_utf8_lossy(&bytes).to_string(),
+        let Some(transfer) = self.tx_transfer.take() else {
+            return;
+        };
+        self.tx_bytes += transfer.sent() as u64;
+        match outcome {
+            Ok(_) => {
+                self.log(LogEntry::tx(transfer.display.clone()), None);
+                if transfer.chunked() {
+                    let summary = format!(
+                        "TX -- {} bytes sent in {} ms",
+                        transfer.total(),
+                        transfer.elapsed().as_millis()
+                    );
+                    self.log(LogEntry::info(summary), None);
+                }
+            }
+            Err(e) => self.log(
+                LogEntry::error(format!(
+                    "TX -- Send failed after {} of {} bytes: {}",
+                    transfer.sent(),
+                    transfer.total(),
+                    e
+                )),
+                None,
+            ),
+        }
+    }
+
+    fn cancel_transfer(&mut self) {
+        if let Some(transfer) = self.tx_transfer.take() {
+            self.tx_bytes += transfer.sent() as u64;
+            self.log(
+                LogEntry::error(format!(
+                    "TX -- Cancelled after {} of {} bytes",
+                    transfer.sent(),
+                    transfer.total()
+                )),
+                None,
+            );
+        }
+    }
+
+    fn start_loopback(&mut self) {
+        let Some(port) = self.port.clone() else {
+            return;
+        };
+        // the pattern must not interleave with a send still going out
+        if self.tx_busy() {
+            return;
+        }
+
+        let loopback = Loopback::new(self.port_settings.baud_rate);
+        // anything half received belongs to the traffic before the test
+        self.rx_buffer.clear();
+        let written = port.lock().unwrap().write_all(loopback.pattern());
+        if let Err(e) = written {
+            self.log(
+                LogEntry::error(format!("Loopback -- Send failed: {}", e)),
+                None,
+            );
+            return;
+        }
+
+        self.tx_bytes += loopback.pattern().len() as u64;
+        self.status = "Loopback test running...".into();
+        self.log(
+            LogEntry::info(format!(
+                "Loopback -- sent {} bytes 00..FF",
+                loopback.pattern().len()
+            )),
+            None,
+        );
+        self.loopback = Some(loopback);
+    }
+
+    // reports once the whole pattern is back or the echo timed out
+    fn loopback_tick(&mut self, ctx: &egui::Context) {
+        let Some(loopback) = &self.loopback else {
+            return;
+        };
+        if !loopback.finished() {
+            ctx.request_repaint_after(loopback.remaining().min(Duration::from_millis(100)));
+            return;
+        }
+
+        let (status, entry) = match loopback.result() {
+            Ok(summary) => (
+                "Loopback test passed",
+                LogEntry::info(format!("Loopback -- PASS, {}", summary)),
+            ),
+            Err(e) => (
+                "Loopback test failed",
+                LogEntry::error(format!("Loopback -- FAIL, {}", e)),
+            ),
         };
+        self.loopback = None;
+        self.status = status.into();
+        self.log(entry, None);
+    }
+
+    fn send_at(&mut self) {
+        let command = self.at_command.trim().to_string();
+        if self.port.is_none() || command.is_empty() || self.tx_busy() {
+            return;
+        }
+        // replies come back as CRLF terminated lines
+        if self.rx_delimiter == RxDelimiter::None {
+            self.rx_delimiter = RxDelimiter::Newline;
+        }
+
+        // through the same transfer as any other send, which logs it and
+        // reports a failed write
+        let bytes = format!("{}\r", command).into_bytes();
+        self.tx_transfer = Some(Transfer::new(bytes, format!("{}\\r", command)));
+        self.transfer_tick();
+
+        self.at_result = None;
+        self.at_request = Some(AtRequest::new(
+            &command,
+            Duration::from_millis(self.at_timeout_ms),
+        ));
+    }
 
-        self.logs.push(format!("{} TX -> {}", ts, display));
+    fn at_tick(&mut self, ctx: &egui::Context) {
+        let Some(request) = &self.at_request else {
+            return;
+        };
+        match request.timed_out() {
+            Some(outcome) => self.finish_at(outcome),
+            None => ctx.request_repaint_after(request.remaining().min(Duration::from_millis(100))),
+        }
+    }
+
+    fn finish_at(&mut self, outcome: Result<String, String>) {
+        let Some(request) = self.at_request.take() else {
+            return;
+        };
+        let entry = match &outcome {
+            Ok(text) => LogEntry::info(format!("AT -- {}: {}", request.command, text)),
+            Err(text) => LogEntry::error(format!("AT -- {}: {}", request.command, text)),
+        };
+        self.log(entry, None);
+        self.at_result = Some(outcome);
     }
 
     pub fn start_read_thread(
         port: Arc<Mutex<Box<dyn SerialPort>>>,
-        tx: Sender<Vec<u8>>,
+        tx: Sender<AppMessage>,
         running: Arc<AtomicBool>,
-    ) {
+        buffer_size: usize,
+    ) -> JoinHandle<()> {
         running.store(true, Ordering::SeqCst);
 
         thread::spawn(move || {
-            let mut buf = [0u8; 256];
+            let mut buf = vec![0u8; buffer_size];
+            // reads that came back full, reported at most every BUFFER_FULL_REPORT
+            let mut full_reads = 0;
+            let mut last_full_report: Option<Instant> = None;
             let mut frame: Vec<u8> = Vec::new();
 
             // setting timeout
             let frame_timeout = Duration::from_millis(30);
             let mut last_recv = Instant::now();
 
+            let mut lost = None;
+
             while running.load(Ordering::SeqCst) {
                 let n = {
                     let mut port = match port.lock() {
@@ -361,11 +1867,26 @@ impl SerialTool {
 
                     match port.read(&mut buf) {
                         Ok(n) => n,
+                        // timeouts are just an idle line
                         Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => 0,
-                        Err(_) => break,
+                        Err(e) => {
+                            lost = Some(e.to_string());
+                            break;
+                        }
                     }
                 };
 
+                if n == buf.len() {
+                    full_reads += 1;
+                    if last_full_report.is_none_or(|at| at.elapsed() >= BUFFER_FULL_REPORT) {
+                        last_full_report = Some(Instant::now());
+                        let _ = tx.send(AppMessage::SerialBufferFull {
+                            reads: std::mem::take(&mut full_reads),
+                            buffer_size,
+                        });
+                    }
+                }
+
                 if n > 0 {
                     frame.extend_from_slice(&buf[..n]);
                     last_recv = Instant::now();
@@ -375,7 +1896,7 @@ impl SerialTool {
                 // timeout -> finish
                 if !frame.is_empty() && last_recv.elapsed() >= frame_timeout {
                     let completed = std::mem::take(&mut frame);
-                    let _ = tx.send(completed);
+                    let _ = tx.send(AppMessage::SerialRx(completed));
                 }
 
                 thread::sleep(Duration::from_millis(2));
@@ -383,8 +1904,52 @@ impl SerialTool {
 
             // reissue residual data
             if !frame.is_empty() {
-                let _ = tx.send(frame);
+                let _ = tx.send(AppMessage::SerialRx(frame));
             }
-        });
+
+            if let Some(e) = lost {
+                let _ = tx.send(AppMessage::SerialLost(e));
+            }
+        })
+    }
+}
+
+// picked per theme, the dark-mode shades would be unreadable on white
+fn direction_color(visuals: &egui::Visuals, direction: Direction) -> egui::Color32 {
+    match (direction, visuals.dark_mode) {
+        (Direction::Tx, true) => egui::Color32::from_rgb(110, 170, 255),
+        (Direction::Tx, false) => egui::Color32::from_rgb(0, 80, 180),
+        (Direction::Rx, true) => egui::Color32::from_rgb(120, 210, 120),
+        (Direction::Rx, false) => egui::Color32::from_rgb(0, 120, 40),
+        (Direction::Info, _) => visuals.weak_text_color(),
+        (Direction::Error, _) => visuals.error_fg_color,
+    }
+}
+
+// monospace `text` with every case-insensitive occurrence of `needle` (already
+// lower-case) marked; ASCII folding keeps the byte offsets of both in step
+fn highlight(
+    ui: &egui::Ui,
+    text: &str,
+    needle: &str,
+    color: egui::Color32,
+) -> egui::text::LayoutJob {
+    let font = egui::TextStyle::Monospace.resolve(ui.style());
+    let plain = egui::TextFormat::simple(font, color);
+    let marked = egui::TextFormat {
+        background: ui.visuals().selection.bg_fill,
+        ..plain.clone()
+    };
+
+    let mut job = egui::text::LayoutJob::default();
+    let haystack = text.to_ascii_lowercase();
+    let mut start = 0;
+    while let Some(pos) = haystack[start..].find(needle) {
+        let at = start + pos;
+        job.append(&text[start..at], 0.0, plain.clone());
+        job.append(&text[at..at + needle.len()], 0.0, marked.clone());
+        start = at + needle.len();
     }
+    job.append(&text[start..], 0.0, plain);
+    job
 }
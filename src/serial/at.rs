@@ -0,0 +1,126 @@
+use std::time::{Duration, Instant};
+
+// offered in the AT panel dropdown, with what they're for
+pub const COMMON_COMMANDS: &[(&str, &str)] = &[
+    ("AT", "Is the module alive?"),
+    ("ATI", "Product information"),
+    ("ATE0", "Echo off"),
+    ("ATE1", "Echo on"),
+    ("AT+GMR", "Firmware version"),
+    ("AT+CGSN", "IMEI"),
+    ("AT+CIMI", "IMSI"),
+    ("AT+CPIN?", "SIM status"),
+    ("AT+CSQ", "Signal quality"),
+    ("AT+CREG?", "Network registration"),
+    ("AT+COPS?", "Operator"),
+    ("AT+CGATT?", "Packet service attached"),
+    ("AT+CFUN?", "Functionality level"),
+    ("AT+RST", "Restart (ESP modules)"),
+];
+
+// An AT command waiting for its final result code. The reader thread
+// delivers RX as usual, the UI feeds the lines in here, so nothing blocks.
+pub struct AtRequest {
+    pub command: String,
+    // intermediate lines, e.g. "+CSQ: 20,99"
+    response: Vec<String>,
+    started: Instant,
+    timeout: Duration,
+}
+
+impl AtRequest {
+    pub fn new(command: &str, timeout: Duration) -> Self {
+        AtRequest {
+            command: command.to_string(),
+            response: Vec::new(),
+            started: Instant::now(),
+            timeout,
+        }
+    }
+
+    // Some once the command is done, with the outcome and elapsed time
+    pub fn feed(&mut self, line: &str) -> Option<Result<String, String>> {
+        let line = line.trim();
+        // blank separators and the module echoing the command back
+        if line.is_empty() || line.eq_ignore_ascii_case(&self.command) {
+            return None;
+        }
+        match final_result(line) {
+            Some(Ok(())) => Some(Ok(self.summary("OK"))),
+            Some(Err(code)) => Some(Err(self.summary(code))),
+            None => {
+                self.response.push(line.to_string());
+                None
+            }
+        }
+    }
+
+    pub fn timed_out(&self) -> Option<Result<String, String>> {
+        if self.started.elapsed() >= self.timeout {
+            Some(Err(self.summary("no response")))
+        } else {
+            None
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.timeout.saturating_sub(self.started.elapsed())
+    }
+
+    fn summary(&self, outcome: &str) -> String {
+        let mut text = format!("{} in {} ms", outcome, self.started.elapsed().as_millis());
+        if !self.response.is_empty() {
+            text.push_str(": ");
+            text.push_str(&self.response.join(" | "));
+        }
+        text
+    }
+}
+
+// final result codes end a command, anything else is part of the response
+fn final_result(line: &str) -> Option<Result<(), &str>> {
+    match line {
+        "OK" => Some(Ok(())),
+        "ERROR" | "NO CARRIER" | "BUSY" | "NO ANSWER" | "NO DIALTONE" => Some(Err(line)),
+        _ if line.starts_with("+CME ERROR") || line.starts_with("+CMS ERROR") => Some(Err(line)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn result_codes() {
+        assert_eq!(final_result("OK"), Some(Ok(())));
+        assert_eq!(final_result("ERROR"), Some(Err("ERROR")));
+        assert_eq!(final_result("+CME ERROR: 10"), Some(Err("+CME ERROR: 10")));
+        assert_eq!(final_result("+CSQ: 20,99"), None);
+    }
+
+    #[test]
+    fn collects_response_and_skips_echo() {
+        let mut request = AtRequest::new("AT+CSQ", Duration::from_secs(1));
+        assert_eq!(request.feed("AT+CSQ\r\n"), None);
+        assert_eq!(request.feed("+CSQ: 20,99\r\n"), None);
+        assert_eq!(request.feed("\r\n"), None);
+
+        let outcome = request.feed("OK\r\n").unwrap().unwrap();
+        assert!(outcome.starts_with("OK in "));
+        assert!(outcome.ends_with(": +CSQ: 20,99"));
+    }
+
+    #[test]
+    fn error_ends_the_command() {
+        let mut request = AtRequest::new("AT+CPIN?", Duration::from_secs(1));
+        let outcome = request.feed("+CME ERROR: 10").unwrap().unwrap_err();
+        assert!(outcome.starts_with("+CME ERROR: 10 in "));
+    }
+
+    #[test]
+    fn times_out() {
+        let request = AtRequest::new("AT", Duration::ZERO);
+        assert!(request.timed_out().unwrap().is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::message::AppMessage;
+use serialport::{DataBits, Parity, StopBits};
+use std::io::Read;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const STANDARD_BAUD_RATES: [u32; 12] = [
+    1200, 2400, 4800, 9600, 14400, 19200, 38400, 57600, 115200, 230400, 460800, 921600,
+];
+
+// how long to listen at each rate
+const LISTEN_TIME: Duration = Duration::from_millis(600);
+
+#[derive(Clone)]
+pub struct BaudCandidate {
+    pub baud_rate: u32,
+    pub bytes: usize,
+    // share of bytes that look like text, 0.0 ..= 1.0
+    pub score: f32,
+}
+
+// Wrong rates mostly produce garbage (or nothing), so the rate whose data is
+// the most printable is usually the right one for text protocols.
+fn score(data: &[u8]) -> f32 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let printable = data
+        .iter()
+        .filter(|b| b.is_ascii_graphic() || matches!(b, b' ' | b'\r' | b'\n' | b'\t'))
+        .count();
+    printable as f32 / data.len() as f32
+}
+
+pub fn start_scan(
+    port_name: String,
+    data_bits: DataBits,
+    parity: Parity,
+    stop_bits: StopBits,
+    tx: Sender<AppMessage>,
+) {
+    thread::spawn(move || {
+        let mut candidates = Vec::new();
+
+        for baud_rate in STANDARD_BAUD_RATES {
+            let _ = tx.send(AppMessage::BaudScanProgress(baud_rate));
+
+            let port = serialport::new(&port_name, baud_rate)
+                .data_bits(data_bits)
+                .parity(parity)
+                .stop_bits(stop_bits)
+                .timeout(Duration::from_millis(50))
+                .open();
+            let Ok(mut port) = port else {
+                continue;
+            };
+
+            let mut data = Vec::new();
+            let mut buf = [0u8; 256];
+            let started = Instant::now();
+            while started.elapsed() < LISTEN_TIME {
+                match port.read(&mut buf) {
+                    Ok(n) => data.extend_from_slice(&buf[..n]),
+                    Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+                    Err(_) => break,
+                }
+            }
+
+            candidates.push(BaudCandidate {
+                baud_rate,
+                bytes: data.len(),
+                score: score(&data),
+            });
+        }
+
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then(b.bytes.cmp(&a.bytes)));
+        let _ = tx.send(AppMessage::BaudScanDone(candidates));
+    });
+}
@@ -0,0 +1,327 @@
+use eframe::egui;
+use std::collections::HashMap;
+use std::fs;
+
+pub enum DiffRow {
+    Same(String, String),
+    Changed(String, String),
+    LeftOnly(String),
+    RightOnly(String),
+}
+
+pub struct LogDiff {
+    pub open: bool,
+    pub left_path: String,
+    pub right_path: String,
+    pub rows: Vec<DiffRow>,
+    pub status: String,
+}
+
+impl LogDiff {
+    pub fn new() -> Self {
+        LogDiff {
+            open: false,
+            left_path: String::new(),
+            right_path: String::new(),
+            rows: Vec::new(),
+            status: String::new(),
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+
+        egui::Window::new("Diff Serial Logs")
+            .open(&mut open)
+            .default_size([900.0, 500.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Old log:");
+                    ui.add(egui::TextEdit::singleline(&mut self.left_path).desired_width(300.0));
+                    ui.label("New log:");
+                    ui.add(egui::TextEdit::singleline(&mut self.right_path).desired_width(300.0));
+
+                    if ui
+                        .button(egui::RichText::new("Compare").color(egui::Color32::BLUE))
+                        .clicked()
+                    {
+                        self.compare();
+                    }
+                });
+
+                ui.monospace(&self.status);
+                ui.separator();
+
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("serial_log_diff")
+                        .striped(true)
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            for row in &self.rows {
+                                let (mark, left, right, color) = match row {
+                                    DiffRow::Same(l, r) => (" ", l.as_str(), r.as_str(), None),
+                                    DiffRow::Changed(l, r) => {
+                                        ("~", l.as_str(), r.as_str(), Some(egui::Color32::ORANGE))
+                                    }
+                                    DiffRow::LeftOnly(l) => {
+                                        ("-", l.as_str(), "", Some(egui::Color32::RED))
+                                    }
+                                    DiffRow::RightOnly(r) => {
+                                        ("+", "", r.as_str(), Some(egui::Color32::DARK_GREEN))
+                                    }
+                                };
+
+                                let text = |s: &str| {
+                                    let t = egui::RichText::new(s).monospace();
+                                    match color {
+                                        Some(c) => t.color(c),
+                                        None => t,
+                                    }
+                                };
+
+                                ui.label(text(mark));
+                                ui.label(text(left));
+                                ui.label(text(right));
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        self.open = open;
+    }
+
+    fn compare(&mut self) {
+        let left = match fs::read_to_string(&self.left_path) {
+            Ok(s) => s,
+            Err(e) => {
+                self.status = format!("Open {} failed: {}", self.left_path, e);
+                return;
+            }
+        };
+        let right = match fs::read_to_string(&self.right_path) {
+            Ok(s) => s,
+            Err(e) => {
+                self.status = format!("Open {} failed: {}", self.right_path, e);
+                return;
+            }
+        };
+
+        let left: Vec<&str> = left.lines().filter(|l| !l.trim().is_empty()).collect();
+        let right: Vec<&str> = right.lines().filter(|l| !l.trim().is_empty()).collect();
+
+        self.rows = diff_frames(&left, &right);
+
+        let changes = self
+            .rows
+            .iter()
+            .filter(|r| !matches!(r, DiffRow::Same(..)))
+            .count();
+        self.status = if changes == 0 {
+            format!("Identical: {} frames", left.len())
+        } else {
+            format!(
+                "{} differences ({} vs {} frames)",
+                changes,
+                left.len(),
+                right.len()
+            )
+        };
+    }
+}
+
+// the timestamp differs between any two sessions, so frames are compared without it
+fn frame_key(line: &str) -> &str {
+    match line.split_once(' ') {
+        Some((ts, rest))
+            if ts.contains(':')
+                && ts
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c == ':' || c == '.') =>
+        {
+            rest
+        }
+        _ => line,
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Step {
+    Same,
+    Removed,
+    Added,
+}
+
+// align frames by longest common subsequence, pairing up removed/added runs as changes
+fn diff_frames<'a>(left: &[&'a str], right: &[&'a str]) -> Vec<DiffRow> {
+    // compare frames as small ids instead of strings
+    let mut ids: HashMap<&str, u32> = HashMap::new();
+    let mut id = |line: &'a str| {
+        let next = ids.len() as u32;
+        *ids.entry(frame_key(line)).or_insert(next)
+    };
+    let a: Vec<u32> = left.iter().map(|&l| id(l)).collect();
+    let b: Vec<u32> = right.iter().map(|&l| id(l)).collect();
+
+    // firmware logs mostly agree, only the middle needs aligning
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let mut steps = vec![Step::Same; prefix];
+    align(
+        &a[prefix..a.len() - suffix],
+        &b[prefix..b.len() - suffix],
+        &mut steps,
+    );
+    steps.extend(std::iter::repeat_n(Step::Same, suffix));
+
+    let mut rows = Vec::new();
+    let mut removed: Vec<String> = Vec::new();
+    let mut added: Vec<String> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    let flush = |rows: &mut Vec<DiffRow>, removed: &mut Vec<String>, added: &mut Vec<String>| {
+        let mut r = removed.drain(..);
+        let mut a = added.drain(..);
+        loop {
+            match (r.next(), a.next()) {
+                (Some(l), Some(rt)) => rows.push(DiffRow::Changed(l, rt)),
+                (Some(l), None) => rows.push(DiffRow::LeftOnly(l)),
+                (None, Some(rt)) => rows.push(DiffRow::RightOnly(rt)),
+                (None, None) => break,
+            }
+        }
+    };
+
+    for step in steps {
+        match step {
+            Step::Same => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push(DiffRow::Same(left[i].to_string(), right[j].to_string()));
+                i += 1;
+                j += 1;
+            }
+            Step::Removed => {
+                removed.push(left[i].to_string());
+                i += 1;
+            }
+            Step::Added => {
+                added.push(right[j].to_string());
+                j += 1;
+            }
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+
+    rows
+}
+
+// Hirschberg's alignment: the LCS in linear memory, so two long captures
+// don't need an n x m table
+fn align(a: &[u32], b: &[u32], steps: &mut Vec<Step>) {
+    if a.is_empty() || b.is_empty() {
+        steps.extend(std::iter::repeat_n(Step::Removed, a.len()));
+        steps.extend(std::iter::repeat_n(Step::Added, b.len()));
+        return;
+    }
+    if a.len() == 1 {
+        match b.iter().position(|&x| x == a[0]) {
+            Some(k) => {
+                steps.extend(std::iter::repeat_n(Step::Added, k));
+                steps.push(Step::Same);
+                steps.extend(std::iter::repeat_n(Step::Added, b.len() - k - 1));
+            }
+            None => {
+                steps.push(Step::Removed);
+                steps.extend(std::iter::repeat_n(Step::Added, b.len()));
+            }
+        }
+        return;
+    }
+
+    let mid = a.len() / 2;
+    let front = lcs_lengths(a[..mid].iter(), b.iter());
+    let back = lcs_lengths(a[mid..].iter().rev(), b.iter().rev());
+    // split b where the two halves together keep the longest subsequence
+    let split = (0..=b.len())
+        .max_by_key(|&k| front[k] + back[b.len() - k])
+        .unwrap_or(0);
+
+    align(&a[..mid], &b[..split], steps);
+    align(&a[mid..], &b[split..], steps);
+}
+
+// LCS length of all of `a` against every prefix of `b`, one row at a time
+fn lcs_lengths<'a>(
+    a: impl Iterator<Item = &'a u32>,
+    b: impl Iterator<Item = &'a u32> + Clone,
+) -> Vec<usize> {
+    let mut row = vec![0; b.clone().count() + 1];
+    for x in a {
+        let mut diagonal = 0;
+        for (k, y) in b.clone().enumerate() {
+            let above = row[k + 1];
+            row[k + 1] = if x == y {
+                diagonal + 1
+            } else {
+                above.max(row[k])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marks(rows: &[DiffRow]) -> String {
+        rows.iter()
+            .map(|r| match r {
+                DiffRow::Same(..) => ' ',
+                DiffRow::Changed(..) => '~',
+                DiffRow::LeftOnly(_) => '-',
+                DiffRow::RightOnly(_) => '+',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn aligns_frames_ignoring_timestamps() {
+        let left = [
+            "10:00:00.000 TX AT",
+            "10:00:00.100 RX OK",
+            "10:00:01.000 RX A",
+            "10:00:02.000 RX B",
+        ];
+        let right = [
+            "11:30:00.000 TX AT",
+            "11:30:00.100 RX OK",
+            "11:30:01.000 RX X",
+            "11:30:02.000 RX B",
+            "11:30:03.000 RX C",
+        ];
+        assert_eq!(marks(&diff_frames(&left, &right)), "  ~ +");
+    }
+
+    #[test]
+    fn finds_the_common_frames_in_the_middle() {
+        let left = ["a", "b", "c", "d", "e", "f"];
+        let right = ["x", "b", "d", "y", "f", "z"];
+        let rows = diff_frames(&left, &right);
+        let same: Vec<&str> = rows
+            .iter()
+            .filter_map(|r| match r {
+                DiffRow::Same(l, _) => Some(l.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(same, ["b", "d", "f"]);
+    }
+}
@@ -0,0 +1,94 @@
+const MAX_ENTRIES: usize = 100;
+
+// Inputs sent from the send box, walked with Up/Down like a shell history.
+pub struct SendHistory {
+    entries: Vec<String>,
+    // entry shown in the input, None while editing a fresh line
+    pos: Option<usize>,
+    // what was typed before browsing, given back past the newest entry
+    draft: String,
+}
+
+impl SendHistory {
+    pub fn new() -> Self {
+        SendHistory {
+            entries: Vec::new(),
+            pos: None,
+            draft: String::new(),
+        }
+    }
+
+    // consecutive repeats (e.g. from Repeat) are kept once
+    pub fn push(&mut self, text: &str) {
+        self.pos = None;
+        if text.trim().is_empty() || self.entries.last().is_some_and(|last| last == text) {
+            return;
+        }
+        self.entries.push(text.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    pub fn older(&mut self, current: &str) -> Option<String> {
+        let pos = match self.pos {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => return None,
+            Some(pos) => pos - 1,
+        };
+        self.pos = Some(pos);
+        Some(self.entries[pos].clone())
+    }
+
+    pub fn newer(&mut self) -> Option<String> {
+        let pos = self.pos?;
+        if pos + 1 < self.entries.len() {
+            self.pos = Some(pos + 1);
+            Some(self.entries[pos + 1].clone())
+        } else {
+            self.pos = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_back_and_forth() {
+        let mut history = SendHistory::new();
+        history.push("AT");
+        history.push("AT+RST");
+
+        assert_eq!(history.older("typing").as_deref(), Some("AT+RST"));
+        assert_eq!(history.older("AT+RST").as_deref(), Some("AT"));
+        assert_eq!(history.older("AT"), None);
+        assert_eq!(history.newer().as_deref(), Some("AT+RST"));
+        assert_eq!(history.newer().as_deref(), Some("typing"));
+        assert_eq!(history.newer(), None);
+    }
+
+    #[test]
+    fn consecutive_repeats_are_kept_once() {
+        let mut history = SendHistory::new();
+        history.push("AT");
+        history.push("AT");
+        history.push("  ");
+
+        assert_eq!(history.older("").as_deref(), Some("AT"));
+        assert_eq!(history.older("AT"), None);
+    }
+
+    #[test]
+    fn empty_history_has_nothing() {
+        let mut history = SendHistory::new();
+        assert_eq!(history.older("x"), None);
+        assert_eq!(history.newer(), None);
+    }
+}
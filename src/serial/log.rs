@@ -0,0 +1,98 @@
+use super::utils::{bytes_to_hex_string, now_timestamp};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Direction {
+    Tx,
+    Rx,
+    // connect and other notes, not tied to a direction
+    Info,
+    // failed sends, lost port and the like
+    Error,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub ts: String,
+    pub direction: Direction,
+    pub text: String,
+}
+
+impl LogEntry {
+    // timestamp is taken when the entry is created, i.e. when it is pushed
+    pub fn new(direction: Direction, text: impl Into<String>) -> Self {
+        LogEntry {
+            ts: now_timestamp(),
+            direction,
+            text: text.into(),
+        }
+    }
+
+    pub fn tx(text: impl Into<String>) -> Self {
+        Self::new(Direction::Tx, text)
+    }
+
+    pub fn rx(text: impl Into<String>) -> Self {
+        Self::new(Direction::Rx, text)
+    }
+
+    pub fn info(text: impl Into<String>) -> Self {
+        Self::new(Direction::Info, text)
+    }
+
+    pub fn error(text: impl Into<String>) -> Self {
+        Self::new(Direction::Error, text)
+    }
+
+    // `needle` must already be lower-case; None lets every direction through
+    pub fn matches(&self, direction: Option<Direction>, needle: &str) -> bool {
+        direction.is_none_or(|d| d == self.direction)
+            && (needle.is_empty() || self.text.to_ascii_lowercase().contains(needle))
+    }
+
+    // "12:00:00.000 TX -> 01 02"
+    pub fn render(&self, timestamps: bool) -> String {
+        let line = match self.direction {
+            Direction::Tx => format!("TX -> {}", self.text),
+            Direction::Rx => format!("RX <- {}", self.text),
+            Direction::Info | Direction::Error => self.text.clone(),
+        };
+        if timestamps {
+            format!("{} {}", self.ts, line)
+        } else {
+            line
+        }
+    }
+}
+
+// Live copy of the log on disk. Every line is flushed so a crash loses nothing.
+pub struct LogFile {
+    pub path: String,
+    writer: BufWriter<File>,
+}
+
+impl LogFile {
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(LogFile {
+            path: path.to_string(),
+            writer: BufWriter::new(file),
+        })
+    }
+
+    // RX lines also carry the raw bytes, so binary traffic survives an ASCII view
+    pub fn write(&mut self, entry: &LogEntry, raw: Option<&[u8]>) -> io::Result<()> {
+        match raw {
+            Some(raw) => writeln!(
+                self.writer,
+                "{} [{}]",
+                entry.render(true),
+                bytes_to_hex_string(raw)
+            )?,
+            None => writeln!(self.writer, "{}", entry.render(true))?,
+        }
+        self.writer.flush()
+    }
+}
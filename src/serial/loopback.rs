@@ -0,0 +1,144 @@
+use std::time::{Duration, Instant};
+
+// on top of the time the pattern needs on the wire
+const ECHO_MARGIN: Duration = Duration::from_secs(1);
+
+// mismatches listed in the report, the count covers the rest
+const MAX_REPORTED: usize = 8;
+
+// Hardware self-test with TX jumpered to RX: 00 01 .. FF goes out and has to
+// come back unchanged. The reader thread delivers the echo as usual, the UI
+// only feeds it in here, so nothing blocks while waiting.
+pub struct Loopback {
+    expected: Vec<u8>,
+    received: Vec<u8>,
+    started: Instant,
+    timeout: Duration,
+}
+
+impl Loopback {
+    pub fn new(baud_rate: u32) -> Self {
+        let expected: Vec<u8> = (0..=255).collect();
+        // about 10 bits per byte with start and stop bit
+        let wire = Duration::from_secs_f64(expected.len() as f64 * 10.0 / baud_rate.max(1) as f64);
+        Loopback {
+            expected,
+            received: Vec::new(),
+            started: Instant::now(),
+            timeout: wire + ECHO_MARGIN,
+        }
+    }
+
+    pub fn pattern(&self) -> &[u8] {
+        &self.expected
+    }
+
+    pub fn feed(&mut self, data: &[u8]) {
+        self.received.extend_from_slice(data);
+    }
+
+    pub fn finished(&self) -> bool {
+        self.received.len() >= self.expected.len() || self.started.elapsed() >= self.timeout
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.timeout.saturating_sub(self.started.elapsed())
+    }
+
+    pub fn result(&self) -> Result<String, String> {
+        check(&self.expected, &self.received).map(|()| {
+            format!(
+                "{} bytes echoed in {} ms",
+                self.expected.len(),
+                self.started.elapsed().as_millis()
+            )
+        })
+    }
+}
+
+fn check(expected: &[u8], received: &[u8]) -> Result<(), String> {
+    if received.is_empty() {
+        return Err("no echo received, is TX jumpered to RX?".into());
+    }
+
+    let mismatches: Vec<usize> = expected
+        .iter()
+        .zip(received)
+        .enumerate()
+        .filter(|(_, (e, r))| e != r)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut problems = Vec::new();
+    if received.len() < expected.len() {
+        problems.push(format!(
+            "only {} of {} bytes came back",
+            received.len(),
+            expected.len()
+        ));
+    } else if received.len() > expected.len() {
+        problems.push(format!(
+            "{} unexpected extra bytes",
+            received.len() - expected.len()
+        ));
+    }
+    if !mismatches.is_empty() {
+        let listed: Vec<String> = mismatches
+            .iter()
+            .take(MAX_REPORTED)
+            .map(|&i| {
+                format!(
+                    "offset {} (sent {:02X}, got {:02X})",
+                    i, expected[i], received[i]
+                )
+            })
+            .collect();
+        let more = match mismatches.len().saturating_sub(MAX_REPORTED) {
+            0 => String::new(),
+            n => format!(" and {} more", n),
+        };
+        problems.push(format!(
+            "{} mismatched bytes: {}{}",
+            mismatches.len(),
+            listed.join(", "),
+            more
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_echo_passes() {
+        let pattern: Vec<u8> = (0..=255).collect();
+        assert_eq!(check(&pattern, &pattern), Ok(()));
+    }
+
+    #[test]
+    fn no_echo_fails() {
+        assert!(check(&[1, 2, 3], &[]).is_err());
+    }
+
+    #[test]
+    fn mismatches_report_offsets() {
+        let err = check(&[0, 1, 2, 3], &[0, 0x81, 2, 0x83]).unwrap_err();
+        assert_eq!(
+            err,
+            "2 mismatched bytes: offset 1 (sent 01, got 81), offset 3 (sent 03, got 83)"
+        );
+    }
+
+    #[test]
+    fn short_echo_fails() {
+        let err = check(&[0, 1, 2, 3], &[0, 1]).unwrap_err();
+        assert_eq!(err, "only 2 of 4 bytes came back");
+    }
+}
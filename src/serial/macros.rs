@@ -0,0 +1,55 @@
+use super::app::SendFormat;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+
+pub const MACROS_FILE: &str = "serial_macros.json";
+
+// a named payload sent with one click, e.g. "Reset" -> AT+RST
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SerialMacro {
+    pub name: String,
+    pub payload: String,
+    pub format: SendFormat,
+}
+
+// the macro being added (index None) or edited
+pub struct MacroDraft {
+    pub index: Option<usize>,
+    pub serial_macro: SerialMacro,
+}
+
+// no file yet is an empty set; a file that can't be read or parsed is an error
+pub fn load(path: &str) -> Result<Vec<SerialMacro>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(serde_json::from_str(&text)?)
+}
+
+pub fn save(path: &str, macros: &[SerialMacro]) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(macros)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_is_empty_but_corrupt_file_is_an_error() {
+        let dir = std::env::temp_dir().join(format!("macros-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("macros.json");
+        let path = path.to_str().unwrap();
+
+        assert!(load(path).unwrap().is_empty());
+        fs::write(path, "[{\"name\": ").unwrap();
+        assert!(load(path).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
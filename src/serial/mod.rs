@@ -1,2 +1,13 @@
 pub mod app;
-pub mod utils;
\ No newline at end of file
+pub mod at;
+pub mod baud;
+pub mod diff;
+pub mod history;
+pub mod log;
+pub mod loopback;
+pub mod macros;
+pub mod nmea;
+pub mod plot;
+pub mod settings;
+pub mod transfer;
+pub mod utils;
@@ -0,0 +1,190 @@
+// NMEA 0183 sentences as sent by GPS modules: "$GPGGA,...*47". Only the
+// fields worth a glance are pulled out; everything else stays in the raw log.
+
+#[derive(Debug, PartialEq)]
+pub enum Checksum {
+    Valid,
+    // the "*hh" suffix is optional in the standard
+    Missing,
+    Invalid { stated: u8, computed: u8 },
+}
+
+pub struct Sentence {
+    // talker + type, e.g. "GPGGA"
+    pub address: String,
+    pub checksum: Checksum,
+    // label / value pairs for GGA and RMC, the raw fields for anything else
+    pub fields: Vec<(&'static str, String)>,
+}
+
+// None when the line isn't a sentence at all
+pub fn parse(line: &str) -> Option<Sentence> {
+    let body = line.trim().strip_prefix('$')?;
+    let (data, checksum) = match body.split_once('*') {
+        Some((data, stated)) => {
+            let computed = data.bytes().fold(0, |acc, b| acc ^ b);
+            let checksum = match u8::from_str_radix(stated.trim(), 16) {
+                Ok(stated) if stated == computed => Checksum::Valid,
+                Ok(stated) => Checksum::Invalid { stated, computed },
+                Err(_) => return None,
+            };
+            (data, checksum)
+        }
+        None => (body, Checksum::Missing),
+    };
+
+    let parts: Vec<&str> = data.split(',').collect();
+    let address = parts[0].to_string();
+    if address.len() < 5 || !address.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let field = |i: usize| parts.get(i).copied().unwrap_or("");
+
+    let fields = match &address[address.len() - 3..] {
+        "GGA" => vec![
+            ("Time", time(field(1))),
+            ("Lat", coordinate(field(2), field(3), 2)),
+            ("Lon", coordinate(field(4), field(5), 3)),
+            ("Fix", gga_fix(field(6)).to_string()),
+            ("Satellites", field(7).to_string()),
+            ("HDOP", field(8).to_string()),
+            (
+                "Altitude",
+                format!("{} {}", field(9), field(10).to_lowercase()),
+            ),
+        ],
+        "RMC" => vec![
+            ("Time", time(field(1))),
+            (
+                "Status",
+                match field(2) {
+                    "A" => "valid",
+                    "V" => "warning",
+                    _ => "-",
+                }
+                .to_string(),
+            ),
+            ("Lat", coordinate(field(3), field(4), 2)),
+            ("Lon", coordinate(field(5), field(6), 3)),
+            ("Speed", knots(field(7))),
+            ("Course", format!("{}°", field(8))),
+            ("Date", date(field(9))),
+        ],
+        _ => parts[1..].iter().map(|p| ("", p.to_string())).collect(),
+    };
+
+    Some(Sentence {
+        address,
+        checksum,
+        fields,
+    })
+}
+
+// "4807.038" + "N" -> "48.117300 N"; `degree_digits` is 2 for latitude, 3 for longitude
+fn coordinate(value: &str, hemisphere: &str, degree_digits: usize) -> String {
+    let parsed = value
+        .get(..degree_digits)
+        .and_then(|d| d.parse::<f64>().ok())
+        .zip(
+            value
+                .get(degree_digits..)
+                .and_then(|m| m.parse::<f64>().ok()),
+        );
+    match parsed {
+        Some((degrees, minutes)) => format!("{:.6} {}", degrees + minutes / 60.0, hemisphere),
+        None => "-".into(),
+    }
+}
+
+// "123519" or "123519.00" -> "12:35:19"
+fn time(value: &str) -> String {
+    match (value.get(0..2), value.get(2..4), value.get(4..)) {
+        (Some(h), Some(m), Some(s)) if !s.is_empty() => format!("{}:{}:{}", h, m, s),
+        _ => "-".into(),
+    }
+}
+
+// "230394" -> "23.03.94"
+fn date(value: &str) -> String {
+    match (value.get(0..2), value.get(2..4), value.get(4..6)) {
+        (Some(d), Some(m), Some(y)) => format!("{}.{}.{}", d, m, y),
+        _ => "-".into(),
+    }
+}
+
+fn knots(value: &str) -> String {
+    match value.parse::<f64>() {
+        Ok(kn) => format!("{} kn ({:.1} km/h)", value, kn * 1.852),
+        Err(_) => "-".into(),
+    }
+}
+
+fn gga_fix(value: &str) -> &'static str {
+    match value {
+        "0" => "none",
+        "1" => "GPS",
+        "2" => "DGPS",
+        "4" => "RTK fixed",
+        "5" => "RTK float",
+        "6" => "estimated",
+        _ => "-",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value<'a>(sentence: &'a Sentence, label: &str) -> &'a str {
+        sentence
+            .fields
+            .iter()
+            .find(|(l, _)| *l == label)
+            .map(|(_, v)| v.as_str())
+            .unwrap()
+    }
+
+    #[test]
+    fn gga_fields() {
+        let s =
+            parse("$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n").unwrap();
+        assert_eq!(s.address, "GPGGA");
+        assert_eq!(s.checksum, Checksum::Valid);
+        assert_eq!(value(&s, "Time"), "12:35:19");
+        assert_eq!(value(&s, "Lat"), "48.117300 N");
+        assert_eq!(value(&s, "Lon"), "11.516667 E");
+        assert_eq!(value(&s, "Fix"), "GPS");
+        assert_eq!(value(&s, "Satellites"), "08");
+    }
+
+    #[test]
+    fn rmc_fields() {
+        let s =
+            parse("$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A").unwrap();
+        assert_eq!(s.checksum, Checksum::Valid);
+        assert_eq!(value(&s, "Status"), "valid");
+        assert_eq!(value(&s, "Speed"), "022.4 kn (41.5 km/h)");
+        assert_eq!(value(&s, "Course"), "084.4°");
+        assert_eq!(value(&s, "Date"), "23.03.94");
+    }
+
+    #[test]
+    fn bad_checksum_is_flagged_not_dropped() {
+        let s = parse("$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*48").unwrap();
+        assert_eq!(
+            s.checksum,
+            Checksum::Invalid {
+                stated: 0x48,
+                computed: 0x47
+            }
+        );
+        assert_eq!(value(&s, "Lat"), "48.117300 N");
+    }
+
+    #[test]
+    fn not_a_sentence() {
+        assert!(parse("OK").is_none());
+        assert!(parse("$,1,2").is_none());
+        assert!(parse("$GPGGA,1*ZZ").is_none());
+    }
+}
@@ -0,0 +1,238 @@
+use egui_plot::{Legend, Line, Plot, PlotPoints};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Instant;
+
+// what each RX line gives to the plot
+#[derive(Clone, Copy, PartialEq)]
+pub enum PlotFields {
+    // the last number on the line, "temp: 23.5" -> 23.5
+    Trailing,
+    // one series per delimited column, "23.5,48,1013"
+    Columns,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ColumnDelimiter {
+    Comma,
+    Semicolon,
+    Tab,
+    Space,
+}
+
+impl ColumnDelimiter {
+    pub const ALL: [ColumnDelimiter; 4] = [
+        ColumnDelimiter::Comma,
+        ColumnDelimiter::Semicolon,
+        ColumnDelimiter::Tab,
+        ColumnDelimiter::Space,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColumnDelimiter::Comma => ",",
+            ColumnDelimiter::Semicolon => ";",
+            ColumnDelimiter::Tab => "Tab",
+            ColumnDelimiter::Space => "Space",
+        }
+    }
+
+    fn char(&self) -> char {
+        match self {
+            ColumnDelimiter::Comma => ',',
+            ColumnDelimiter::Semicolon => ';',
+            ColumnDelimiter::Tab => '\t',
+            ColumnDelimiter::Space => ' ',
+        }
+    }
+}
+
+// Numbers from RX text lines over time, like the Arduino Serial Plotter.
+// Series are keyed by column (0 in Trailing mode); points are (seconds since
+// the first sample, value) and the oldest drop off past max_points.
+pub struct SerialPlot {
+    pub open: bool,
+    pub fields: PlotFields,
+    pub delimiter: ColumnDelimiter,
+    // 1-based columns to plot, "1, 3"; empty plots every numeric column
+    pub columns: String,
+    pub max_points: usize,
+    // keep the newest data in view; off after panning to look back
+    pub follow: bool,
+    series: BTreeMap<usize, VecDeque<[f64; 2]>>,
+    started: Option<Instant>,
+}
+
+impl SerialPlot {
+    pub fn new() -> Self {
+        SerialPlot {
+            open: false,
+            fields: PlotFields::Trailing,
+            delimiter: ColumnDelimiter::Comma,
+            columns: String::new(),
+            max_points: 1000,
+            follow: true,
+            series: BTreeMap::new(),
+            started: None,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.series.clear();
+        self.started = None;
+    }
+
+    pub fn push_line(&mut self, line: &str) {
+        let values = match self.fields {
+            PlotFields::Trailing => trailing_number(line).map(|v| (0, v)).into_iter().collect(),
+            PlotFields::Columns => columns(
+                line,
+                self.delimiter.char(),
+                &selected_columns(&self.columns),
+            ),
+        };
+        if values.is_empty() {
+            return;
+        }
+
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let t = started.elapsed().as_secs_f64();
+        for (column, value) in values {
+            let points = self.series.entry(column).or_default();
+            points.push_back([t, value]);
+            while points.len() > self.max_points {
+                points.pop_front();
+            }
+        }
+    }
+
+    pub fn ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+
+        egui::Window::new("Serial Plotter")
+            .open(&mut open)
+            .default_size([700.0, 400.0])
+            .show(ctx, |ui| {
+                let before = (self.fields, self.delimiter, self.columns.clone());
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.fields, PlotFields::Trailing, "Trailing number");
+                    ui.radio_value(&mut self.fields, PlotFields::Columns, "Columns");
+
+                    if self.fields == PlotFields::Columns {
+                        ui.label("Delimiter");
+                        egui::ComboBox::from_id_salt("plot_delimiter")
+                            .width(60.0)
+                            .selected_text(self.delimiter.label())
+                            .show_ui(ui, |ui| {
+                                for delimiter in ColumnDelimiter::ALL {
+                                    ui.selectable_value(
+                                        &mut self.delimiter,
+                                        delimiter,
+                                        delimiter.label(),
+                                    );
+                                }
+                            });
+                        ui.label("Plot");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.columns)
+                                .hint_text("all")
+                                .desired_width(80.0),
+                        )
+                        .on_hover_text("Columns to plot, counted from 1: \"1, 3\"");
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("History");
+                    ui.add(
+                        egui::DragValue::new(&mut self.max_points)
+                            .range(10..=100_000)
+                            .suffix(" pts"),
+                    );
+                    ui.checkbox(&mut self.follow, "Follow")
+                        .on_hover_text("Keep the newest values in view, Y scaled to fit");
+                    if ui.button("Clear").clicked() {
+                        self.clear();
+                    }
+                    let points = self.series.values().map(|s| s.len()).max().unwrap_or(0);
+                    ui.label(format!("{} series, {} points", self.series.len(), points));
+                });
+
+                if before != (self.fields, self.delimiter, self.columns.clone()) {
+                    self.clear();
+                }
+
+                let fields = self.fields;
+                Plot::new("serial_plot")
+                    .legend(Legend::default())
+                    .x_axis_label("s")
+                    .show(ui, |plot_ui| {
+                        if self.follow {
+                            plot_ui.set_auto_bounds(egui::Vec2b::TRUE);
+                        }
+                        for (column, points) in &self.series {
+                            let name = match fields {
+                                PlotFields::Trailing => "value".to_string(),
+                                PlotFields::Columns => format!("column {}", column + 1),
+                            };
+                            let points: PlotPoints = points.iter().copied().collect();
+                            plot_ui.line(Line::new(name, points));
+                        }
+                    });
+            });
+
+        self.open = open;
+    }
+}
+
+// "1, 3" -> [0, 2]; anything that isn't a column number is skipped
+fn selected_columns(text: &str) -> Vec<usize> {
+    text.split([',', ' '])
+        .filter_map(|s| s.trim().parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .map(|n| n - 1)
+        .collect()
+}
+
+// (column, value) of each field holding a number, only `selected` ones if given
+fn columns(line: &str, delimiter: char, selected: &[usize]) -> Vec<(usize, f64)> {
+    line.trim()
+        .split(delimiter)
+        .enumerate()
+        .filter(|(i, _)| selected.is_empty() || selected.contains(i))
+        .filter_map(|(i, field)| trailing_number(field).map(|v| (i, v)))
+        .collect()
+}
+
+// the last number in the text, so labels like "temp:" or "V=" are skipped
+fn trailing_number(text: &str) -> Option<f64> {
+    text.split(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E')))
+        .filter(|token| !token.is_empty())
+        .filter_map(|token| token.parse::<f64>().ok())
+        .next_back()
+        .filter(|v| v.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailing_number_skips_labels() {
+        assert_eq!(trailing_number("23.5\r\n"), Some(23.5));
+        assert_eq!(trailing_number("temp: -4.25"), Some(-4.25));
+        assert_eq!(trailing_number("V=3.3V"), Some(3.3));
+        assert_eq!(trailing_number("ready"), None);
+    }
+
+    #[test]
+    fn columns_pick_selected_fields() {
+        let line = "23.5,48,x,1013\r\n";
+        assert_eq!(columns(line, ',', &[]), [(0, 23.5), (1, 48.0), (3, 1013.0)]);
+        assert_eq!(
+            columns(line, ',', &selected_columns("1, 4")),
+            [(0, 23.5), (3, 1013.0)]
+        );
+        assert_eq!(columns("t:21 h:40", ' ', &[]), [(0, 21.0), (1, 40.0)]);
+    }
+}
@@ -0,0 +1,154 @@
+use super::baud::STANDARD_BAUD_RATES;
+use serialport::{DataBits, FlowControl, Parity, StopBits};
+
+// Port choice and line settings, shared by the Serial tab and Modbus RTU so a
+// new option only has to be added here.
+pub struct SerialPortSettings {
+    pub available_ports: Vec<String>,
+    pub selected_port: Option<String>,
+    pub baud_rate: u32,
+    // free entry instead of the standard rate list
+    pub baud_custom: bool,
+    pub data_bits: DataBits,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    pub flow_control: FlowControl,
+}
+
+impl SerialPortSettings {
+    pub fn new() -> Self {
+        SerialPortSettings {
+            available_ports: list_ports(),
+            selected_port: None,
+            baud_rate: 9600,
+            baud_custom: false,
+            data_bits: DataBits::Eight,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            flow_control: FlowControl::None,
+        }
+    }
+
+    pub fn refresh_ports(&mut self) {
+        self.available_ports = list_ports();
+    }
+
+    pub fn builder(&self, port: &str) -> serialport::SerialPortBuilder {
+        serialport::new(port, self.baud_rate)
+            .data_bits(self.data_bits)
+            .parity(self.parity)
+            .stop_bits(self.stop_bits)
+            .flow_control(self.flow_control)
+    }
+}
+
+fn list_ports() -> Vec<String> {
+    serialport::available_ports()
+        .map(|ports| ports.into_iter().map(|p| p.port_name).collect())
+        .unwrap_or_default()
+}
+
+// port picker plus baud / data bits / parity / stop bits / flow control;
+// `id` keeps the combo boxes apart when several tools show it
+pub fn serial_settings_ui(ui: &mut egui::Ui, id: &str, settings: &mut SerialPortSettings) {
+    ui.horizontal(|ui| {
+        if ui
+            .button(egui::RichText::new("Refresh Ports").color(egui::Color32::BLUE))
+            .clicked()
+        {
+            settings.refresh_ports();
+            settings.selected_port = None;
+        }
+
+        egui::ComboBox::from_id_salt((id, "port"))
+            .width(220.0)
+            .selected_text(
+                settings
+                    .selected_port
+                    .clone()
+                    .unwrap_or_else(|| "Select Port".into()),
+            )
+            .show_ui(ui, |ui| {
+                for p in &settings.available_ports {
+                    if ui
+                        .selectable_label(settings.selected_port.as_deref() == Some(p), p)
+                        .clicked()
+                    {
+                        settings.selected_port = Some(p.clone());
+                    }
+                }
+            });
+    });
+
+    ui.add_space(6.0);
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(egui::RichText::new("Baud rate:").strong());
+        let custom = settings.baud_custom || !STANDARD_BAUD_RATES.contains(&settings.baud_rate);
+        egui::ComboBox::from_id_salt((id, "baud_rate"))
+            .width(80.0)
+            .selected_text(if custom {
+                "Custom".to_string()
+            } else {
+                settings.baud_rate.to_string()
+            })
+            .show_ui(ui, |ui| {
+                for rate in STANDARD_BAUD_RATES {
+                    if ui
+                        .selectable_label(!custom && settings.baud_rate == rate, rate.to_string())
+                        .clicked()
+                    {
+                        settings.baud_rate = rate;
+                        settings.baud_custom = false;
+                    }
+                }
+                // keeps the current rate as the starting point
+                if ui.selectable_label(custom, "Custom").clicked() {
+                    settings.baud_custom = true;
+                }
+            });
+        if custom {
+            ui.add(
+                egui::DragValue::new(&mut settings.baud_rate)
+                    .speed(100)
+                    .range(1..=4_000_000),
+            );
+        }
+
+        ui.separator();
+
+        ui.label(egui::RichText::new("Data bits:").strong());
+        ui.radio_value(&mut settings.data_bits, DataBits::Five, "5");
+        ui.radio_value(&mut settings.data_bits, DataBits::Six, "6");
+        ui.radio_value(&mut settings.data_bits, DataBits::Seven, "7");
+        ui.radio_value(&mut settings.data_bits, DataBits::Eight, "8");
+
+        ui.separator();
+
+        ui.label(egui::RichText::new("Parity:").strong());
+        ui.radio_value(&mut settings.parity, Parity::None, "None");
+        ui.radio_value(&mut settings.parity, Parity::Odd, "Odd");
+        ui.radio_value(&mut settings.parity, Parity::Even, "Even");
+
+        ui.separator();
+
+        ui.label(egui::RichText::new("Stop bits:").strong());
+        ui.radio_value(&mut settings.stop_bits, StopBits::One, "1");
+        ui.radio_value(&mut settings.stop_bits, StopBits::Two, "2");
+
+        ui.separator();
+
+        ui.label(egui::RichText::new("Flow control:").strong());
+        ui.radio_value(&mut settings.flow_control, FlowControl::None, "None");
+        ui.radio_value(
+            &mut settings.flow_control,
+            FlowControl::Software,
+            "Software",
+        );
+        ui.radio_value(
+            &mut settings.flow_control,
+            FlowControl::Hardware,
+            "Hardware",
+        );
+    });
+}
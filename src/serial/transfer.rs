@@ -0,0 +1,147 @@
+use std::io::{ErrorKind, Write};
+use std::time::{Duration, Instant};
+
+// bytes handed to the port per write, so a big payload shows progress and
+// the reader thread gets the port in between
+const CHUNK: usize = 256;
+
+// give up when flow control holds the line this long
+const STALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+// A send in progress. A write may take only part of a chunk, or nothing while
+// CTS / XOFF holds the line, so the UI steps it until every byte is out.
+pub struct Transfer {
+    bytes: Vec<u8>,
+    sent: usize,
+    // what the log shows once it's done
+    pub display: String,
+    started: Instant,
+    last_progress: Instant,
+    stall_timeout: Duration,
+}
+
+impl Transfer {
+    pub fn new(bytes: Vec<u8>, display: String) -> Self {
+        Transfer {
+            bytes,
+            sent: 0,
+            display,
+            started: Instant::now(),
+            last_progress: Instant::now(),
+            stall_timeout: STALL_TIMEOUT,
+        }
+    }
+
+    // one chunk; Ok(true) once everything is sent, Err when the port failed
+    // or stopped taking data
+    pub fn step<W: Write + ?Sized>(&mut self, port: &mut W) -> Result<bool, String> {
+        let end = (self.sent + CHUNK).min(self.bytes.len());
+        match port.write(&self.bytes[self.sent..end]) {
+            Ok(n) if n > 0 => {
+                self.sent += n;
+                self.last_progress = Instant::now();
+            }
+            Ok(_) => {}
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted
+                ) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+
+        if self.done() {
+            return Ok(true);
+        }
+        if self.last_progress.elapsed() >= self.stall_timeout {
+            return Err(format!(
+                "no progress for {} ms, flow control may be holding the line",
+                self.stall_timeout.as_millis()
+            ));
+        }
+        Ok(false)
+    }
+
+    pub fn done(&self) -> bool {
+        self.sent == self.bytes.len()
+    }
+
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+
+    pub fn total(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    // larger than one write, worth a progress bar
+    pub fn chunked(&self) -> bool {
+        self.bytes.len() > CHUNK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    // takes at most `per_write` bytes, refusing every other call
+    struct Throttled {
+        written: Vec<u8>,
+        per_write: usize,
+        block_next: bool,
+    }
+
+    impl Write for Throttled {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.block_next = !self.block_next;
+            if !self.block_next {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            let n = buf.len().min(self.per_write);
+            self.written.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn short_writes_and_would_block_still_send_everything() {
+        let payload: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        let mut port = Throttled {
+            written: Vec::new(),
+            per_write: 100,
+            block_next: false,
+        };
+        let mut transfer = Transfer::new(payload.clone(), String::new());
+
+        let mut steps = 0;
+        while !transfer.step(&mut port).unwrap() {
+            steps += 1;
+            assert!(steps < 100);
+        }
+        assert_eq!(port.written, payload);
+        assert_eq!(transfer.sent(), 1000);
+    }
+
+    #[test]
+    fn stalled_line_reports_what_was_sent() {
+        let mut port = Throttled {
+            written: Vec::new(),
+            per_write: 0,
+            block_next: false,
+        };
+        let mut transfer = Transfer::new(vec![0; 10], String::new());
+        transfer.stall_timeout = Duration::ZERO;
+
+        assert!(transfer.step(&mut port).is_err());
+        assert_eq!(transfer.sent(), 0);
+    }
+}
@@ -1,29 +1,126 @@
-pub fn parse_hex_string(input: &str) -> Result<Vec<u8>, String> {
-    let cleaned: String = input
-        .chars()
-        .filter(|c| c.is_ascii_hexdigit())
-        .collect();
+use std::fmt;
 
-    if cleaned.len() % 2 != 0 {
-        return Err("HEX length must be even".into());
+#[derive(Debug, PartialEq)]
+pub enum HexParseError {
+    // number of hex digits, after separators are removed
+    OddLength(usize),
+    // 0-based character position in the original input
+    InvalidChar { ch: char, position: usize },
+}
+
+impl fmt::Display for HexParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexParseError::OddLength(n) => write!(f, "odd number of hex digits ({})", n),
+            HexParseError::InvalidChar { ch, position } => {
+                write!(f, "non-hex char '{}' at position {}", ch, position)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HexParseError {}
+
+// "01 0a FF" -> [0x01, 0x0A, 0xFF]; whitespace is ignored, anything else must be a hex digit
+pub fn parse_hex_string(input: &str) -> Result<Vec<u8>, HexParseError> {
+    let mut digits = Vec::new();
+    for (position, ch) in input.chars().enumerate() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        match ch.to_digit(16) {
+            Some(d) => digits.push(d as u8),
+            None => return Err(HexParseError::InvalidChar { ch, position }),
+        }
     }
 
-    let mut bytes = Vec::new();
-    for i in (0..cleaned.len()).step_by(2) {
-        let byte = u8::from_str_radix(&cleaned[i..i + 2], 16)
-            .map_err(|_| "Invalid HEX")?;
-        bytes.push(byte);
+    if digits.len() % 2 != 0 {
+        return Err(HexParseError::OddLength(digits.len()));
     }
 
-    Ok(bytes)
+    Ok(digits.chunks(2).map(|p| (p[0] << 4) | p[1]).collect())
+}
+
+// how hex output is joined, e.g. to paste it straight into code
+#[derive(Clone, Copy, PartialEq)]
+pub enum HexStyle {
+    // "01 0A FF"
+    Spaced,
+    // "010AFF"
+    Compact,
+    // "0x01, 0x0A, 0xFF"
+    Prefixed,
+}
+
+impl HexStyle {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HexStyle::Spaced => "01 0A",
+            HexStyle::Compact => "010A",
+            HexStyle::Prefixed => "0x01, 0x0A",
+        }
+    }
+
+    pub fn format(&self, bytes: &[u8]) -> String {
+        match self {
+            HexStyle::Spaced => format_hex(bytes, " ", false),
+            HexStyle::Compact => format_hex(bytes, "", false),
+            HexStyle::Prefixed => format_hex(bytes, ", ", true),
+        }
+    }
+}
+
+// upper-case byte pairs joined by `sep`, each with "0x" in front if `prefix`
+pub fn format_hex(bytes: &[u8], sep: &str, prefix: bool) -> String {
+    let prefix = if prefix { "0x" } else { "" };
+    bytes
+        .iter()
+        .map(|b| format!("{}{:02X}", prefix, b))
+        .collect::<Vec<_>>()
+        .join(sep)
 }
 
 pub fn bytes_to_hex_string(bytes: &[u8]) -> String {
+    format_hex(bytes, " ", false)
+}
+
+// printable ASCII as is, everything else as '.'
+pub fn bytes_to_ascii_dots(bytes: &[u8]) -> String {
     bytes
         .iter()
-        .map(|b| format!("{:02X}", b))
+        .map(|b| {
+            if b.is_ascii_graphic() || *b == b' ' {
+                *b as char
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+// one row of a dump with its offset: "00000010  48 65 6C 6C 6F  ...  |Hello|"
+pub fn hexdump_row(offset: usize, chunk: &[u8]) -> String {
+    format!(
+        "{:08X}  {:<47}  |{}|",
+        offset,
+        bytes_to_hex_string(chunk),
+        bytes_to_ascii_dots(chunk)
+    )
+}
+
+// 16 bytes per line: "48 65 6C 6C 6F        |Hello|"
+pub fn hexdump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .map(|chunk| {
+            format!(
+                "{:<47}  |{}|",
+                bytes_to_hex_string(chunk),
+                bytes_to_ascii_dots(chunk)
+            )
+        })
         .collect::<Vec<_>>()
-        .join(" ")
+        .join("\n")
 }
 
 pub fn now_timestamp() -> String {
@@ -31,3 +128,80 @@ pub fn now_timestamp() -> String {
 
     Local::now().format("%H:%M:%S%.3f").to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_input_is_empty() {
+        assert_eq!(parse_hex_string(""), Ok(vec![]));
+        assert_eq!(parse_hex_string("   "), Ok(vec![]));
+    }
+
+    #[test]
+    fn odd_length_is_rejected() {
+        assert_eq!(parse_hex_string("123"), Err(HexParseError::OddLength(3)));
+        assert_eq!(parse_hex_string("01 2"), Err(HexParseError::OddLength(3)));
+    }
+
+    #[test]
+    fn spaces_are_ignored() {
+        assert_eq!(parse_hex_string("01 02  03"), Ok(vec![1, 2, 3]));
+        assert_eq!(parse_hex_string("0 1\t02\n"), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn mixed_case_is_accepted() {
+        assert_eq!(parse_hex_string("aB Cd eF"), Ok(vec![0xAB, 0xCD, 0xEF]));
+    }
+
+    #[test]
+    fn invalid_char_reports_position() {
+        let err = parse_hex_string("01 G2").unwrap_err();
+        assert_eq!(
+            err,
+            HexParseError::InvalidChar {
+                ch: 'G',
+                position: 3
+            }
+        );
+        assert_eq!(err.to_string(), "non-hex char 'G' at position 3");
+    }
+
+    #[test]
+    fn hexdump_row_has_offset_and_gutter() {
+        assert_eq!(
+            hexdump_row(0x10, b"Hi\x00"),
+            format!("00000010  {:<47}  |Hi.|", "48 69 00")
+        );
+        let full: Vec<u8> = (0x41..0x51).collect();
+        assert_eq!(
+            hexdump_row(0, &full),
+            "00000000  41 42 43 44 45 46 47 48 49 4A 4B 4C 4D 4E 4F 50  |ABCDEFGHIJKLMNOP|"
+        );
+    }
+
+    #[test]
+    fn hex_is_space_separated_by_default() {
+        assert_eq!(bytes_to_hex_string(&[0x01, 0x0A, 0xFF]), "01 0A FF");
+        assert_eq!(HexStyle::Spaced.format(&[0x01, 0x0A, 0xFF]), "01 0A FF");
+        assert_eq!(bytes_to_hex_string(&[]), "");
+    }
+
+    #[test]
+    fn hex_without_separator() {
+        assert_eq!(format_hex(&[0x01, 0x0A, 0xFF], "", false), "010AFF");
+        assert_eq!(HexStyle::Compact.format(&[0xDE, 0xAD]), "DEAD");
+    }
+
+    #[test]
+    fn hex_with_prefix() {
+        assert_eq!(
+            format_hex(&[0x01, 0x0A, 0xFF], ", ", true),
+            "0x01, 0x0A, 0xFF"
+        );
+        assert_eq!(HexStyle::Prefixed.format(&[0x7F]), "0x7F");
+        assert_eq!(format_hex(&[0x01, 0x02], "", true), "0x010x02");
+    }
+}
@@ -0,0 +1,67 @@
+use crate::modbus::app::ModbusFunction;
+use crate::serial::log::LogEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+// Snapshot written periodically so a crash or forced close doesn't lose a capture.
+// The file is removed on a clean exit, so finding one at startup means recovery.
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct Session {
+    pub saved_at: String,
+
+    pub serial_port: Option<String>,
+    pub serial_baud_rate: u32,
+    pub serial_logs: VecDeque<LogEntry>,
+
+    pub modbus_ip: String,
+    pub modbus_port: u16,
+    pub modbus_slave_id: u8,
+    // None in snapshots written before it was saved
+    pub modbus_function: Option<ModbusFunction>,
+    pub modbus_address: u16,
+    pub modbus_quantity: u16,
+    pub modbus_data: Vec<u16>,
+    pub modbus_logs: Vec<String>,
+}
+
+impl Session {
+    // next to eframe's app.ron; the temp dir is shared and gets swept
+    pub fn path() -> Option<PathBuf> {
+        eframe::storage_dir(crate::APP_FULL).map(|dir| dir.join("session.json"))
+    }
+
+    pub fn load() -> Option<Self> {
+        let text = fs::read_to_string(Self::path()?).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    // serialization happens on the caller, only the disk write is moved off
+    // the UI thread; join the handle before clear() so it can't land after
+    pub fn save_in_background(&self) -> Option<JoinHandle<()>> {
+        let path = Self::path()?;
+        let json = serde_json::to_string(self).ok()?;
+
+        Some(thread::spawn(move || {
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            let tmp = path.with_extension("json.tmp");
+            if fs::write(&tmp, json).is_ok() {
+                let _ = fs::rename(tmp, path);
+            }
+        }))
+    }
+
+    pub fn clear() {
+        if let Some(path) = Self::path() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
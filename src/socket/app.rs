@@ -0,0 +1,407 @@
+use crate::message::AppMessage;
+use crate::serial::app::{RxDisplay, SendFormat};
+use crate::serial::log::LogEntry;
+use crate::serial::utils::{bytes_to_hex_string, hexdump, parse_hex_string};
+use std::collections::VecDeque;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+// while connected, so received data shows up without moving the mouse
+const REPAINT_INTERVAL: Duration = Duration::from_millis(100);
+
+// log entries kept, the oldest are dropped past this
+const MAX_LOG_LINES: usize = 10_000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+enum Socket {
+    Tcp(TcpStream),
+    // connected, so it only exchanges datagrams with host:port
+    Udp(UdpSocket),
+}
+
+impl Socket {
+    async fn open(protocol: Protocol, addr: String) -> Result<Self, String> {
+        let open = async {
+            match protocol {
+                Protocol::Tcp => TcpStream::connect(&addr).await.map(Socket::Tcp),
+                Protocol::Udp => {
+                    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+                    socket.connect(&addr).await?;
+                    Ok(Socket::Udp(socket))
+                }
+            }
+        };
+        match tokio::time::timeout(CONNECT_TIMEOUT, open).await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(_) => Err("connect timeout".into()),
+        }
+    }
+
+    async fn recv(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Socket::Tcp(stream) => match stream.read(buf).await? {
+                0 => Err(std::io::ErrorKind::UnexpectedEof.into()),
+                n => Ok(n),
+            },
+            Socket::Udp(socket) => socket.recv(buf).await,
+        }
+    }
+
+    async fn send(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        match self {
+            Socket::Tcp(stream) => stream.write_all(bytes).await,
+            Socket::Udp(socket) => socket.send(bytes).await.map(|_| ()),
+        }
+    }
+
+    // one task owns the socket; it ends when the UI drops its sender
+    async fn run(mut self, mut out: UnboundedReceiver<Vec<u8>>, tx: Sender<AppMessage>) {
+        let mut buf = [0u8; 4096];
+        loop {
+            let result = tokio::select! {
+                received = self.recv(&mut buf) => received.map(|n| {
+                    let _ = tx.send(AppMessage::SocketRx(buf[..n].to_vec()));
+                }),
+                bytes = out.recv() => match bytes {
+                    Some(bytes) => self.send(&bytes).await.map(|()| {
+                        let _ = tx.send(AppMessage::SocketSent(bytes.len()));
+                    }),
+                    None => return,
+                },
+            };
+            if let Err(e) = result {
+                let message = match e.kind() {
+                    std::io::ErrorKind::UnexpectedEof => "Closed by peer".to_string(),
+                    _ => e.to_string(),
+                };
+                let _ = tx.send(AppMessage::SocketLost(message));
+                return;
+            }
+        }
+    }
+}
+
+pub struct SocketTool {
+    pub protocol: Protocol,
+    pub host: String,
+    pub port: u16,
+
+    pub send_format: SendFormat,
+    pub input_text: String,
+    pub rx_display: RxDisplay,
+
+    pub logs: VecDeque<LogEntry>,
+    // counted once the socket task wrote them
+    pub tx_bytes: u64,
+    pub rx_bytes: u64,
+
+    // set between Connect and Disconnect, bytes to send go through it
+    out: Option<UnboundedSender<Vec<u8>>>,
+    pub connected: bool,
+
+    tx: Sender<AppMessage>,
+    rx: Receiver<AppMessage>,
+    // None when the runtime failed to start; the tab is then disabled
+    rt: Option<tokio::runtime::Runtime>,
+
+    pub status: String,
+}
+
+impl SocketTool {
+    pub fn new() -> Self {
+        let (rt, status) = match tokio::runtime::Runtime::new() {
+            Ok(rt) => (Some(rt), "Disconnected".to_string()),
+            Err(e) => (None, format!("Async runtime unavailable: {}", e)),
+        };
+
+        let (tx, rx) = channel::<AppMessage>();
+
+        Self {
+            protocol: Protocol::Tcp,
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+
+            send_format: SendFormat::Ascii,
+            input_text: String::new(),
+            rx_display: RxDisplay::Ascii,
+
+            logs: VecDeque::new(),
+            tx_bytes: 0,
+            rx_bytes: 0,
+
+            out: None,
+            connected: false,
+            tx,
+            rx,
+            rt,
+
+            status,
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        if self.rt.is_none() {
+            ui.vertical_centered(|ui| {
+                ui.add_space(40.0);
+                ui.label(
+                    egui::RichText::new("Socket is disabled")
+                        .strong()
+                        .color(egui::Color32::RED),
+                );
+                ui.label(&self.status);
+            });
+            return;
+        }
+
+        ui.vertical(|ui| {
+            self.ui_connection(ui);
+            self.ui_sender(ui);
+            self.ui_logs(ui);
+            self.ui_status(ui);
+        });
+
+        let messages: Vec<AppMessage> = self.rx.try_iter().collect();
+        for msg in messages {
+            self.handle_message(msg);
+        }
+        if self.out.is_some() {
+            ui.ctx().request_repaint_after(REPAINT_INTERVAL);
+        }
+    }
+
+    fn handle_message(&mut self, msg: AppMessage) {
+        match msg {
+            // the outcome of a socket that Disconnect already dropped
+            AppMessage::SocketConnected(_) | AppMessage::SocketLost(_) if self.out.is_none() => {}
+            AppMessage::SocketConnected(Ok(())) => {
+                self.connected = true;
+                self.status = format!("Connected to {}:{}", self.host, self.port);
+                self.log(LogEntry::info(self.status.clone()));
+            }
+            AppMessage::SocketConnected(Err(e)) | AppMessage::SocketLost(e) => {
+                self.out = None;
+                self.connected = false;
+                self.status = e.clone();
+                self.log(LogEntry::error(e));
+            }
+            AppMessage::SocketSent(n) => self.tx_bytes += n as u64,
+            AppMessage::SocketRx(data) => {
+                self.rx_bytes += data.len() as u64;
+                let display = match self.rx_display {
+                    RxDisplay::Hex => bytes_to_hex_string(&data),
+                    RxDisplay::Ascii => String::from_utf8_lossy(&data)
+                        .replace('\r', "\\r")
+                        .replace('\n', "\\n"),
+                    RxDisplay::Both | RxDisplay::Dump => hexdump(&data),
+                };
+                self.log(LogEntry::rx(display));
+            }
+            _ => {}
+        }
+    }
+
+    fn ui_connection(&mut self, ui: &mut egui::Ui) {
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.label(egui::RichText::new("Connection").strong());
+
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(self.out.is_none(), |ui| {
+                    ui.selectable_value(&mut self.protocol, Protocol::Tcp, "TCP");
+                    ui.selectable_value(&mut self.protocol, Protocol::Udp, "UDP");
+
+                    ui.separator();
+
+                    ui.label("Host");
+                    ui.text_edit_singleline(&mut self.host);
+
+                    ui.label("Port");
+                    ui.add(egui::DragValue::new(&mut self.port));
+                });
+
+                ui.separator();
+
+                if ui
+                    .add_enabled(
+                        self.out.is_none(),
+                        egui::Button::new(
+                            egui::RichText::new("Connect").color(egui::Color32::BLUE),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.connect();
+                }
+                if ui
+                    .add_enabled(
+                        self.out.is_some(),
+                        egui::Button::new(
+                            egui::RichText::new("Disconnect").color(egui::Color32::RED),
+                        ),
+                    )
+                    .clicked()
+                {
+                    self.disconnect();
+                }
+
+                if self.connected {
+                    ui.label(egui::RichText::new("● Connected").color(egui::Color32::DARK_GREEN));
+                } else if self.out.is_some() {
+                    ui.label("Connecting...");
+                }
+            });
+        });
+    }
+
+    fn ui_sender(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.send_format, SendFormat::Hex, "HEX");
+            ui.radio_value(&mut self.send_format, SendFormat::Ascii, "ASCII");
+
+            ui.separator();
+
+            ui.add_sized(
+                [ui.available_width() - 120.0, 24.0],
+                egui::TextEdit::multiline(&mut self.input_text).hint_text(match self.send_format {
+                    SendFormat::Hex => "48 65 6C 6C 6F",
+                    SendFormat::Ascii => "Hello",
+                }),
+            );
+
+            if ui
+                .add_enabled(
+                    self.connected,
+                    egui::Button::new(egui::RichText::new("Send").color(egui::Color32::BLUE)),
+                )
+                .on_disabled_hover_text("Connect first")
+                .clicked()
+            {
+                self.send();
+            }
+
+            if ui.button("Clear").clicked() {
+                self.logs.clear();
+                self.tx_bytes = 0;
+                self.rx_bytes = 0;
+            }
+        });
+    }
+
+    fn ui_logs(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("RX");
+            ui.radio_value(&mut self.rx_display, RxDisplay::Hex, "HEX");
+            ui.radio_value(&mut self.rx_display, RxDisplay::Ascii, "ASCII");
+            ui.radio_value(&mut self.rx_display, RxDisplay::Both, "Both");
+
+            ui.separator();
+            ui.monospace(format!("TX: {} B  RX: {} B", self.tx_bytes, self.rx_bytes));
+        });
+
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .auto_shrink([false; 2])
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for log in &self.logs {
+                        ui.monospace(log.render(true));
+                    }
+                });
+        });
+    }
+
+    fn ui_status(&mut self, ui: &mut egui::Ui) {
+        egui::TopBottomPanel::bottom("socket_status").show(ui.ctx(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new("Status: ").color(egui::Color32::RED));
+                ui.monospace(&self.status);
+            });
+        });
+    }
+
+    fn connect(&mut self) {
+        if self.out.is_some() {
+            return;
+        }
+        let Some(rt) = &self.rt else {
+            return;
+        };
+
+        let (out_tx, out_rx) = unbounded_channel();
+        self.out = Some(out_tx);
+        self.status = "Connecting...".into();
+
+        let protocol = self.protocol;
+        let addr = format!("{}:{}", self.host.trim(), self.port);
+        let tx = self.tx.clone();
+        rt.spawn(async move {
+            match Socket::open(protocol, addr).await {
+                Ok(socket) => {
+                    let _ = tx.send(AppMessage::SocketConnected(Ok(())));
+                    socket.run(out_rx, tx).await;
+                }
+                Err(e) => {
+                    let _ = tx.send(AppMessage::SocketConnected(Err(e)));
+                }
+            }
+        });
+    }
+
+    // dropping the sender ends the socket task, which closes the socket
+    pub fn disconnect(&mut self) {
+        if self.out.take().is_none() {
+            return;
+        }
+
+        self.connected = false;
+        self.status = "Disconnected".into();
+        self.log(LogEntry::info("Disconnected"));
+    }
+
+    fn send(&mut self) {
+        let Some(out) = &self.out else {
+            return;
+        };
+
+        let bytes = match self.send_format {
+            SendFormat::Hex => match parse_hex_string(&self.input_text) {
+                Ok(b) => b,
+                Err(e) => {
+                    self.log(LogEntry::error(format!("TX -- Invalid hex: {}", e)));
+                    return;
+                }
+            },
+            SendFormat::Ascii => self.input_text.as_bytes().to_vec(),
+        };
+        if bytes.is_empty() {
+            return;
+        }
+
+        let display = match self.send_format {
+            SendFormat::Hex => bytes_to_hex_string(&bytes),
+            SendFormat::Ascii => String::from_utf8_lossy(&bytes)
+                .replace('\r', "\\r")
+                .replace('\n', "\\n"),
+        };
+        if out.send(bytes).is_ok() {
+            self.log(LogEntry::tx(display));
+        }
+    }
+
+    fn log(&mut self, entry: LogEntry) {
+        self.logs.push_back(entry);
+        if self.logs.len() > MAX_LOG_LINES {
+            self.logs.pop_front();
+        }
+    }
+}